@@ -0,0 +1,102 @@
+//! Compare the lock free halves from `RingBuffer::split` with the `BiLock` based halves from
+//! `AsyncReadExt::split`.
+//!
+//! Run with `cargo bench --bench split`.
+//
+use
+{
+	futures_ringbuf :: { RingBuffer                                                     } ,
+	futures         :: { AsyncRead, AsyncWrite, AsyncReadExt, AsyncWriteExt, executor::block_on, join } ,
+	criterion       :: { criterion_group, criterion_main, Criterion, Throughput         } ,
+	std             :: { thread                                                         } ,
+};
+
+
+const TOTAL   : usize = 64 * 1024;
+const CHUNK   : usize = 64;
+const CAPACITY: usize = 1024;
+
+
+async fn send( mut writer: impl AsyncWrite + Unpin )
+{
+	let data = [7u8; CHUNK];
+
+	for _ in 0..TOTAL/CHUNK
+	{
+		writer.write_all( &data ).await.expect( "write" );
+	}
+
+	writer.close().await.expect( "close" );
+}
+
+
+async fn receive( mut reader: impl AsyncRead + Unpin )
+{
+	let mut buf  = [0u8; CHUNK];
+	let mut read = 0;
+
+	loop
+	{
+		match reader.read( &mut buf ).await.expect( "read" )
+		{
+			0 => break,
+			n => read += n,
+		}
+	}
+
+	assert_eq!( read, TOTAL );
+}
+
+
+fn same_task( c: &mut Criterion )
+{
+	let mut group = c.benchmark_group( "same task" );
+	group.throughput( Throughput::Bytes( TOTAL as u64 ) );
+
+	group.bench_function( "BiLock", |b| b.iter( ||
+	{
+		let (reader, writer) = AsyncReadExt::split( RingBuffer::<u8>::new( CAPACITY ) );
+
+		block_on( async { join!( send( writer ), receive( reader ) ) } );
+	}));
+
+	group.bench_function( "lock free", |b| b.iter( ||
+	{
+		let (reader, writer) = RingBuffer::<u8>::new( CAPACITY ).split();
+
+		block_on( async { join!( send( writer ), receive( reader ) ) } );
+	}));
+
+	group.finish();
+}
+
+
+fn threads( c: &mut Criterion )
+{
+	let mut group = c.benchmark_group( "threads" );
+	group.throughput( Throughput::Bytes( TOTAL as u64 ) );
+
+	group.bench_function( "BiLock", |b| b.iter( ||
+	{
+		let (reader, writer) = AsyncReadExt::split( RingBuffer::<u8>::new( CAPACITY ) );
+
+		let writer = thread::spawn( move || block_on( send( writer ) ) );
+		block_on( receive( reader ) );
+		writer.join().expect( "join writer" );
+	}));
+
+	group.bench_function( "lock free", |b| b.iter( ||
+	{
+		let (reader, writer) = RingBuffer::<u8>::new( CAPACITY ).split();
+
+		let writer = thread::spawn( move || block_on( send( writer ) ) );
+		block_on( receive( reader ) );
+		writer.join().expect( "join writer" );
+	}));
+
+	group.finish();
+}
+
+
+criterion_group!( benches, same_task, threads );
+criterion_main! ( benches );
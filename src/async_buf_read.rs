@@ -0,0 +1,175 @@
+use crate::{ import::*, RingBuffer, Consumer };
+
+
+impl AsyncBufRead for RingBuffer<u8>
+{
+	/// Returns the contiguous readable part of the underlying ringbuffer without copying. When the data
+	/// wraps around the end of the buffer, only the first part is returned. After consuming it, the
+	/// next call will return the rest.
+	///
+	/// Will return Poll::Pending when the buffer is empty. Will be woken up by the AsyncWrite impl when new
+	/// data is written or the writer is closed.
+	///
+	/// When the buffer is closed and empty, this will return an empty slice.
+	///
+	/// This method is infallible.
+	//
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		let this = self.get_mut();
+
+		if this.consumer.is_empty()
+		{
+			if this.closed
+			{
+				// Signals end of stream.
+				//
+				return Poll::Ready( Ok(&[]) );
+			}
+
+			// Store this waker so that the writer can wake us up after they wrote something.
+			//
+			this.read_waker.replace( cx.waker().clone() );
+
+			return Poll::Pending;
+		}
+
+		Poll::Ready( Ok( first_slice( &this.consumer ) ) )
+	}
+
+
+	/// Mark `amt` bytes as read. If a writer is waiting for place in the buffer, it will be woken up.
+	//
+	fn consume( mut self: Pin<&mut Self>, amt: usize )
+	{
+		if self.consumer.skip( amt ) != 0
+		{
+			if let Some(waker) = self.write_waker.take()
+			{
+				waker.wake();
+			}
+		}
+	}
+}
+
+
+/// The first non-empty contiguous slice of data in the consumer, if any.
+//
+pub(crate) fn first_slice( consumer: &Consumer<u8> ) -> &[u8]
+{
+	let (first, second) = consumer.as_slices();
+
+	if first.is_empty() { second } else { first }
+}
+
+
+
+
+#[cfg(test)]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ filling from a buffer with data
+	// ✔ consume wakes up a pending writer
+	// ✔ fill_buf on empty buffer sets the waker
+	// ✔ data wrapping around the end of the buffer
+	// ✔ read_line
+	// ✔ fill_buf on a closed empty buffer
+	//
+	use crate::{ import::{ *, assert_eq }, RingBuffer };
+	use futures::AsyncBufReadExt;
+
+	#[test]
+	//
+	fn fill_and_consume() { block_on( async
+	{
+		let mut ring = RingBuffer::<u8>::new(4);
+
+		AsyncWriteExt::write_all( &mut ring, b"abcd" ).await.expect( "write" );
+
+		assert_eq!( ring.fill_buf().await.unwrap(), b"abcd" );
+
+		// Filling twice without consuming returns the same data.
+		//
+		assert_eq!( ring.fill_buf().await.unwrap(), b"abcd" );
+
+
+		// Full buffer, writer should be pending.
+		//
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( AsyncWrite::poll_write( Pin::new( &mut ring ), &mut cx, b"ef" ).is_pending() );
+
+		ring.consume_unpin( 3 );
+
+		assert_eq!( count, 1 );
+		assert!( ring.write_waker.is_none() );
+		assert_eq!( ring.len(), 1 );
+
+
+		// Now the data wraps around the end of the buffer.
+		//
+		AsyncWriteExt::write_all( &mut ring, b"ef" ).await.expect( "write" );
+
+		assert_eq!( ring.fill_buf().await.unwrap(), b"d" );
+		ring.consume_unpin( 1 );
+
+		assert_eq!( ring.fill_buf().await.unwrap(), b"ef" );
+		ring.consume_unpin( 2 );
+
+		assert!( ring.is_empty() );
+
+
+		// Empty buffer
+		//
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( AsyncBufRead::poll_fill_buf( Pin::new( &mut ring ), &mut cx ).is_pending() );
+		assert!( ring.read_waker.is_some() );
+
+		AsyncWriteExt::write_all( &mut ring, b"g" ).await.expect( "write" );
+
+		assert_eq!( count, 1 );
+		assert_eq!( ring.fill_buf().await.unwrap(), b"g" );
+	})}
+
+
+	#[test]
+	//
+	fn read_line() { block_on( async
+	{
+		let mut ring = RingBuffer::<u8>::new(32);
+		let mut line = String::new();
+
+		AsyncWriteExt::write_all( &mut ring, b"Hello World\nSecond" ).await.expect( "write" );
+		ring.close().await.expect( "close" );
+
+		ring.read_line( &mut line ).await.expect( "read_line" );
+		assert_eq!( line, "Hello World\n" );
+
+		line.clear();
+
+		ring.read_line( &mut line ).await.expect( "read_line" );
+		assert_eq!( line, "Second" );
+	})}
+
+
+	#[test]
+	//
+	fn closed_fill_buf() { block_on( async
+	{
+		let mut ring = RingBuffer::<u8>::new(2);
+
+		ring.close().await.expect( "close" );
+
+		assert!( ring.fill_buf().await.unwrap().is_empty() );
+
+		// try again, just in case
+		//
+		assert!( ring.fill_buf().await.unwrap().is_empty() );
+	})}
+}
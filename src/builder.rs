@@ -0,0 +1,382 @@
+use crate::{ import::*, RingBuffer, Endpoint, Clock, ReadHalf, WriteHalf, throttle::Throttle, recorder::{ Recorder, Tap } };
+
+#[ cfg( feature = "sketchy" ) ] use crate::corruption::{ Corruption, Corruptor };
+
+
+/// The default capacity of the buffer for one direction of a connection, in bytes.
+//
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+
+const DEFAULT_A_ADDR: SocketAddr = SocketAddr::V4( SocketAddrV4::new( Ipv4Addr::LOCALHOST, 49152 ) );
+const DEFAULT_B_ADDR: SocketAddr = SocketAddr::V4( SocketAddrV4::new( Ipv4Addr::LOCALHOST, 49153 ) );
+
+
+/// Configuration for one direction of a connection created by [`EndpointBuilder`], that is the data
+/// written by one endpoint and read by the other.
+//
+#[ derive( Debug, Clone ) ]
+//
+pub struct DirectionConfig
+{
+	pub(crate) capacity : usize            ,
+	pub(crate) latency  : Option<Duration> ,
+	pub(crate) bandwidth: Option<u64>      ,
+	pub(crate) burst    : Option<u64>      ,
+
+	#[ cfg( feature = "sketchy" ) ]
+	//
+	pub(crate) corruption: Option<(Corruption, u64)>,
+}
+
+
+impl DirectionConfig
+{
+	/// Create the configuration for a direction with a buffer of `capacity` bytes. When the buffer is
+	/// full, the writer will be pending until the reader consumes data.
+	//
+	pub fn new( capacity: usize ) -> Self
+	{
+		Self
+		{
+			capacity            ,
+			latency  : None     ,
+			bandwidth: None     ,
+			burst    : None     ,
+
+			#[ cfg( feature = "sketchy" ) ]
+			//
+			corruption: None,
+		}
+	}
+
+
+	/// Set the size of the buffer in bytes.
+	//
+	pub fn capacity( mut self, capacity: usize ) -> Self
+	{
+		self.capacity = capacity;
+		self
+	}
+
+
+	/// Data written at time _t_ only becomes readable at _t + latency_. A reader waiting for data will be
+	/// woken up when it arrives, using the clock set with [`EndpointBuilder::clock`].
+	///
+	/// Closing the writer is not delayed. The reader will see the end of the stream once it has read all
+	/// data, including the data that is still in transit.
+	//
+	pub fn latency( mut self, latency: Duration ) -> Self
+	{
+		self.latency = Some( latency );
+		self
+	}
+
+
+	/// Limit how fast data can be written to `bytes_per_sec`. When the limit is reached, `poll_write`
+	/// returns `Poll::Pending` and the writer will be woken up when it may write again, using the clock
+	/// set with [`EndpointBuilder::clock`].
+	///
+	/// The limit is enforced with a token bucket that starts full. Unless set with [`DirectionConfig::burst`],
+	/// the bucket holds a tenth of a second worth of data.
+	///
+	/// # Panics
+	///
+	/// When creating the endpoints if `bytes_per_sec` is zero.
+	//
+	pub fn bandwidth( mut self, bytes_per_sec: u64 ) -> Self
+	{
+		self.bandwidth = Some( bytes_per_sec );
+		self
+	}
+
+
+	/// Corrupt the data in this direction, to test checksums and error handling. A [`Dictator`](crate::Dictator)
+	/// created with `seed` decides what gets corrupted.
+	//
+	#[ cfg( feature = "sketchy" ) ]
+	//
+	pub fn corrupt( mut self, corruption: Corruption, seed: u64 ) -> Self
+	{
+		self.corruption = Some(( corruption, seed ));
+		self
+	}
+
+
+	/// How many bytes can be written at once before the bandwidth limit kicks in. Has no effect without
+	/// [`DirectionConfig::bandwidth`].
+	///
+	/// # Panics
+	///
+	/// When creating the endpoints if `bytes` is zero.
+	//
+	pub fn burst( mut self, bytes: u64 ) -> Self
+	{
+		self.burst = Some( bytes );
+		self
+	}
+}
+
+
+impl Default for DirectionConfig
+{
+	/// A direction with a buffer of [`DEFAULT_CAPACITY`] bytes.
+	//
+	fn default() -> Self
+	{
+		Self::new( DEFAULT_CAPACITY )
+	}
+}
+
+
+
+/// Create a pair of connected [`Endpoint`]s, configuring each direction independently. The endpoints
+/// are called _a_ and _b_, and the directions _a to b_ (written by a, read by b) and _b to a_.
+///
+/// [`Endpoint::pair`] is a shortcut for a builder that only sets the capacities.
+///
+/// ```
+/// use futures_ringbuf::{ EndpointBuilder, DirectionConfig };
+///
+/// let (server, client) = EndpointBuilder::new()
+///
+///    .a_name( "server" )
+///    .b_name( "client" )
+///    .a_to_b( DirectionConfig::new( 64 ) )
+///    .b_to_a( DirectionConfig::new( 16 ) )
+///    .build()
+/// ;
+///
+/// assert_eq!( server.name(), "server" );
+/// ```
+//
+#[ derive( Debug, Clone ) ]
+//
+pub struct EndpointBuilder
+{
+	a_name  : String                   ,
+	b_name  : String                   ,
+	a_addr  : SocketAddr               ,
+	b_addr  : SocketAddr               ,
+	a_to_b  : DirectionConfig          ,
+	b_to_a  : DirectionConfig          ,
+	clock   : Option< Arc<dyn Clock> > ,
+	recorder: Option< Recorder       > ,
+}
+
+
+impl EndpointBuilder
+{
+	/// Create a builder with endpoints called "a" and "b" and the default configuration for both directions.
+	/// The endpoints have the addresses `127.0.0.1:49152` and `127.0.0.1:49153`.
+	//
+	pub fn new() -> Self
+	{
+		Self
+		{
+			a_name  : "a".to_string()           ,
+			b_name  : "b".to_string()           ,
+			a_addr  : DEFAULT_A_ADDR             ,
+			b_addr  : DEFAULT_B_ADDR             ,
+			a_to_b  : DirectionConfig::default() ,
+			b_to_a  : DirectionConfig::default() ,
+			clock   : None                       ,
+			recorder: None                       ,
+		}
+	}
+
+
+	/// The name of the first endpoint. It is used in log messages.
+	//
+	pub fn a_name( mut self, name: impl Into<String> ) -> Self
+	{
+		self.a_name = name.into();
+		self
+	}
+
+
+	/// The name of the second endpoint. It is used in log messages.
+	//
+	pub fn b_name( mut self, name: impl Into<String> ) -> Self
+	{
+		self.b_name = name.into();
+		self
+	}
+
+
+	/// The address of the first endpoint. It is the `local_addr` of the first endpoint and the `peer_addr`
+	/// of the second.
+	//
+	pub fn a_addr( mut self, addr: SocketAddr ) -> Self
+	{
+		self.a_addr = addr;
+		self
+	}
+
+
+	/// The address of the second endpoint. It is the `local_addr` of the second endpoint and the `peer_addr`
+	/// of the first.
+	//
+	pub fn b_addr( mut self, addr: SocketAddr ) -> Self
+	{
+		self.b_addr = addr;
+		self
+	}
+
+
+	/// Configure the data written by the first endpoint and read by the second.
+	//
+	pub fn a_to_b( mut self, config: DirectionConfig ) -> Self
+	{
+		self.a_to_b = config;
+		self
+	}
+
+
+	/// Configure the data written by the second endpoint and read by the first.
+	//
+	pub fn b_to_a( mut self, config: DirectionConfig ) -> Self
+	{
+		self.b_to_a = config;
+		self
+	}
+
+
+	/// The clock used for time based behavior, like latency and bandwidth. Defaults to a [`SystemClock`](crate::SystemClock).
+	/// On WASM there is no default, so you must set a clock if you use time based behavior.
+	//
+	pub fn clock( mut self, clock: impl Clock + 'static ) -> Self
+	{
+		self.clock = Some( Arc::new( clock ) );
+		self
+	}
+
+
+	/// Record all data written on both endpoints. If a clock is set or used for time based behavior, the
+	/// records are timestamped with it.
+	//
+	pub fn recorder( mut self, recorder: Recorder ) -> Self
+	{
+		self.recorder = Some( recorder );
+		self
+	}
+
+
+	/// Create the endpoints.
+	///
+	/// # Panics
+	///
+	/// On WASM, when time based behavior is configured without setting a clock.
+	//
+	pub fn build( mut self ) -> (Endpoint, Endpoint)
+	{
+		log::trace!( "Creating endpoints {:?} and {:?}: {:?}", self.a_name, self.b_name, self );
+
+		let (ab_reader, mut ab_writer) = self.direction( &self.a_to_b.clone() );
+		let (ba_reader, mut ba_writer) = self.direction( &self.b_to_a.clone() );
+
+		if let Some(recorder) = &self.recorder
+		{
+			ab_writer = ab_writer.with_tap( Tap::new( recorder.clone(), &self.a_name, &self.b_name, self.clock.clone() ) );
+			ba_writer = ba_writer.with_tap( Tap::new( recorder.clone(), &self.b_name, &self.a_name, self.clock.clone() ) );
+		}
+
+		#[ cfg( feature = "sketchy" ) ]
+		//
+		{
+			if let Some((corruption, seed)) = self.a_to_b.corruption.clone()
+			{
+				let label = format!( "{} -> {}", self.a_name, self.b_name );
+				ab_writer = ab_writer.with_corruptor( Corruptor::new( corruption, seed, label ) );
+			}
+
+			if let Some((corruption, seed)) = self.b_to_a.corruption.clone()
+			{
+				let label = format!( "{} -> {}", self.b_name, self.a_name );
+				ba_writer = ba_writer.with_corruptor( Corruptor::new( corruption, seed, label ) );
+			}
+		}
+
+		(
+			Endpoint{ name: self.a_name, local_addr: self.a_addr, peer_addr: self.b_addr, writer: ab_writer, reader: ba_reader },
+			Endpoint{ name: self.b_name, local_addr: self.b_addr, peer_addr: self.a_addr, writer: ba_writer, reader: ab_reader },
+		)
+	}
+}
+
+
+impl EndpointBuilder
+{
+	fn direction( &mut self, config: &DirectionConfig ) -> (ReadHalf<u8>, WriteHalf<u8>)
+	{
+		let buffer  = RingBuffer::<u8>::new( config.capacity );
+		let latency = config.latency.map( |latency| (latency, self.clock_or_default()) );
+
+		let (reader, mut writer) = buffer.split_with_latency( latency );
+
+		if let Some(rate) = config.bandwidth
+		{
+			let burst = config.burst.unwrap_or( ( rate / 10 ).max( 1 ) );
+
+			writer = writer.with_throttle( Throttle::new( rate, burst, self.clock_or_default() ) );
+		}
+
+		(reader, writer)
+	}
+
+
+	/// The configured clock, or the default one.
+	//
+	fn clock_or_default( &mut self ) -> Arc<dyn Clock>
+	{
+		self.clock.get_or_insert_with( default_clock ).clone()
+	}
+
+
+	/// The address of the second endpoint, which is the server side of the connections of a
+	/// [`MockNetwork`](crate::MockNetwork).
+	//
+	pub(crate) fn server_addr( &self ) -> SocketAddr
+	{
+		self.b_addr
+	}
+
+
+	/// Create the default clock now if time based behavior needs one, so all endpoints built from clones
+	/// of this builder share it, instead of each pair starting a clock of its own.
+	//
+	pub(crate) fn share_clock( &mut self )
+	{
+		let timed = |config: &DirectionConfig| config.latency.is_some() || config.bandwidth.is_some();
+
+		if timed( &self.a_to_b ) || timed( &self.b_to_a )
+		{
+			self.clock_or_default();
+		}
+	}
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+pub(crate) fn default_clock() -> Arc<dyn Clock>
+{
+	Arc::new( crate::SystemClock::new() )
+}
+
+
+#[ cfg( target_arch = "wasm32" ) ]
+//
+pub(crate) fn default_clock() -> Arc<dyn Clock>
+{
+	panic!( "futures_ringbuf: there is no default clock on WASM, set one with EndpointBuilder::clock" );
+}
+
+
+impl Default for EndpointBuilder
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
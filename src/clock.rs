@@ -0,0 +1,362 @@
+use crate::import::*;
+
+
+/// A source of time for the time based behavior of endpoints, like latency. Implement this to plug in the
+/// timer of your executor, or use [`SystemClock`], which works with any executor. In tests, use a
+/// [`VirtualClock`] to control time.
+///
+/// Time is expressed as the duration elapsed since a fixed starting point of the clock, so clocks that
+/// don't follow wall clock time can be implemented easily.
+//
+pub trait Clock: fmt::Debug + Send + Sync
+{
+	/// The time elapsed since the starting point of this clock.
+	//
+	fn now( &self ) -> Duration;
+
+	/// Wake up `waker` once [`Clock::now`] has reached `deadline`. Waking up early is fine, endpoints
+	/// will look at the time again and ask for a new wake up if needed.
+	//
+	fn wake_at( &self, deadline: Duration, waker: Waker );
+}
+
+
+impl<C: Clock + ?Sized> Clock for Arc<C>
+{
+	fn now( &self ) -> Duration
+	{
+		(**self).now()
+	}
+
+	fn wake_at( &self, deadline: Duration, waker: Waker )
+	{
+		(**self).wake_at( deadline, waker )
+	}
+}
+
+
+
+/// A [`Clock`] that follows wall clock time. Wake ups are done by a background thread, so this works
+/// with any executor. The thread is started when the first wake up is requested and stops once all
+/// clones of the clock are dropped. Not available on WASM.
+//
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+#[ derive( Debug, Clone ) ]
+//
+pub struct SystemClock
+{
+	inner: Arc<SystemClockInner>,
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+#[ derive( Debug ) ]
+//
+struct SystemClockInner
+{
+	start  : Instant                         ,
+	timers : Mutex< Vec<(Duration, Waker)> > ,
+	changed: Condvar                         ,
+	thread : Once                            ,
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+impl SystemClock
+{
+	/// Create a new clock. [`Clock::now`] will be counted from this moment.
+	//
+	pub fn new() -> Self
+	{
+		Self
+		{
+			inner: Arc::new( SystemClockInner
+			{
+				start  : Instant::now()      ,
+				timers : Mutex::new( vec![] ) ,
+				changed: Condvar::new()      ,
+				thread : Once::new()         ,
+			})
+		}
+	}
+
+
+	/// Wake up expired timers and wait for the next one. Runs on the background thread as long as
+	/// the clock is alive.
+	//
+	fn run( inner: Weak<SystemClockInner> )
+	{
+		// Upper bound on how long we sleep, so we notice when the clock is dropped.
+		//
+		const IDLE: Duration = Duration::from_millis( 100 );
+
+		while let Some(inner) = inner.upgrade()
+		{
+			let mut timers = inner.timers.lock().unwrap_or_else( PoisonError::into_inner );
+			let now        = inner.start.elapsed();
+
+			let (expired, waiting): (Vec<_>, Vec<_>) = std::mem::take( &mut *timers )
+
+				.into_iter()
+				.partition( |(deadline, _)| *deadline <= now )
+			;
+
+			*timers = waiting;
+
+			if expired.is_empty()
+			{
+				let next  = timers.iter().map( |(deadline, _)| *deadline - now ).min();
+				let sleep = next.unwrap_or( IDLE ).min( IDLE );
+
+				let _ = inner.changed.wait_timeout( timers, sleep );
+			}
+
+			else
+			{
+				// Don't wake while holding the lock, the waker might call back into the clock.
+				//
+				drop( timers );
+
+				for (_, waker) in expired
+				{
+					waker.wake();
+				}
+			}
+		}
+	}
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+impl Default for SystemClock
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+impl Clock for SystemClock
+{
+	fn now( &self ) -> Duration
+	{
+		self.inner.start.elapsed()
+	}
+
+
+	fn wake_at( &self, deadline: Duration, waker: Waker )
+	{
+		if deadline <= self.now()
+		{
+			return waker.wake();
+		}
+
+		self.inner.timers.lock().unwrap_or_else( PoisonError::into_inner ).push( (deadline, waker) );
+		self.inner.changed.notify_one();
+
+		let inner = Arc::downgrade( &self.inner );
+
+		self.inner.thread.call_once( move ||
+		{
+			std::thread::Builder::new()
+
+				.name( "futures_ringbuf::SystemClock".to_string() )
+				.spawn( move || Self::run( inner ) )
+				.expect( "spawn SystemClock thread" )
+			;
+		});
+	}
+}
+
+
+
+/// A [`Clock`] that only moves when you call [`VirtualClock::advance`]. This makes tests of time based
+/// behavior instant and reproducible. Clones share the same time, so keep a clone to advance the clock
+/// given to [`EndpointBuilder::clock`](crate::EndpointBuilder::clock).
+///
+/// ```
+/// use futures_ringbuf::{ VirtualClock, Clock };
+/// use std::time::Duration;
+///
+/// let clock = VirtualClock::new();
+///
+/// clock.advance( Duration::from_millis( 50 ) );
+///
+/// assert_eq!( clock.now(), Duration::from_millis( 50 ) );
+/// ```
+//
+#[ derive( Debug, Clone, Default ) ]
+//
+pub struct VirtualClock
+{
+	inner: Arc< Mutex<VirtualClockInner> >,
+}
+
+
+#[ derive( Debug, Default ) ]
+//
+struct VirtualClockInner
+{
+	now   : Duration               ,
+	timers: Vec<(Duration, Waker)> ,
+}
+
+
+impl VirtualClock
+{
+	/// Create a new clock at time zero.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// Move time forward by `by` and wake up all tasks whose deadline has passed.
+	//
+	pub fn advance( &self, by: Duration )
+	{
+		let mut inner = self.inner.lock().unwrap_or_else( PoisonError::into_inner );
+
+		inner.now += by;
+
+		let now = inner.now;
+
+		let (expired, waiting): (Vec<_>, Vec<_>) = std::mem::take( &mut inner.timers )
+
+			.into_iter()
+			.partition( |(deadline, _)| *deadline <= now )
+		;
+
+		inner.timers = waiting;
+
+		// Don't wake while holding the lock, the waker might call back into the clock.
+		//
+		drop( inner );
+
+		for (_, waker) in expired
+		{
+			waker.wake();
+		}
+	}
+
+
+	/// The number of wake ups that have been requested and are still waiting for their deadline.
+	//
+	pub fn pending_timers( &self ) -> usize
+	{
+		self.inner.lock().unwrap_or_else( PoisonError::into_inner ).timers.len()
+	}
+}
+
+
+impl Clock for VirtualClock
+{
+	fn now( &self ) -> Duration
+	{
+		self.inner.lock().unwrap_or_else( PoisonError::into_inner ).now
+	}
+
+
+	fn wake_at( &self, deadline: Duration, waker: Waker )
+	{
+		let mut inner = self.inner.lock().unwrap_or_else( PoisonError::into_inner );
+
+		if deadline <= inner.now
+		{
+			drop( inner );
+			return waker.wake();
+		}
+
+		inner.timers.push( (deadline, waker) );
+	}
+}
+
+
+
+#[ cfg( all( test, not( target_arch = "wasm32" ) ) ) ]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ time moves forward
+	// ✔ wakers are woken after the deadline
+	// ✔ expired deadlines wake immediately
+	// ✔ virtual clock only wakes tasks when advanced past their deadline
+	//
+	use super::*;
+	use crate::import::assert_eq;
+	use std::sync::mpsc;
+
+
+	#[test]
+	//
+	fn system_clock()
+	{
+		let clock = SystemClock::new();
+		let start = clock.now();
+
+		let (tx, rx) = mpsc::channel();
+
+		let waker = futures::task::waker( Arc::new( Notify( Mutex::new( tx ) ) ) );
+
+		clock.wake_at( start + Duration::from_millis( 20 ), waker.clone() );
+		clock.wake_at( start, waker );
+
+		// The expired one wakes right away.
+		//
+		rx.recv_timeout( Duration::from_millis( 10 ) ).expect( "immediate wake up" );
+
+		rx.recv_timeout( Duration::from_secs( 5 ) ).expect( "wake up" );
+
+		assert!( clock.now() >= start + Duration::from_millis( 20 ) );
+	}
+
+
+	#[test]
+	//
+	fn virtual_clock()
+	{
+		let clock          = VirtualClock::new();
+		let (waker, count) = new_count_waker();
+
+		clock.wake_at( Duration::from_millis( 20 ), waker.clone() );
+		clock.wake_at( Duration::from_millis( 10 ), waker.clone() );
+		clock.wake_at( Duration::ZERO             , waker         );
+
+		assert_eq!( count.get()          , 1 );
+		assert_eq!( clock.pending_timers(), 2 );
+
+		clock.advance( Duration::from_millis( 9 ) );
+		assert_eq!( count.get(), 1 );
+
+		clock.advance( Duration::from_millis( 1 ) );
+		assert_eq!( count.get()          , 2 );
+		assert_eq!( clock.pending_timers(), 1 );
+
+		clock.advance( Duration::from_secs( 1 ) );
+		assert_eq!( count.get()          , 3 );
+		assert_eq!( clock.pending_timers(), 0 );
+
+		assert_eq!( clock.now(), Duration::from_millis( 1010 ) );
+	}
+
+
+	struct Notify( Mutex< mpsc::Sender<()> > );
+
+	impl ArcWake for Notify
+	{
+		fn wake_by_ref( arc_self: &Arc<Self> )
+		{
+			let _ = arc_self.0.lock().unwrap().send(());
+		}
+	}
+}
@@ -0,0 +1,147 @@
+use crate::{ Dictator, dictator::check_probability };
+
+
+/// The probabilities of corrupting the data in one direction of a pair of endpoints. Set it with
+/// [`DirectionConfig::corrupt`](crate::DirectionConfig::corrupt). By default nothing is corrupted.
+///
+/// Every corruption is logged at debug level with its offset in the data written, so a failing seed can be diagnosed.
+///
+/// ```
+/// use futures_ringbuf::{ EndpointBuilder, DirectionConfig, Corruption, Dictator };
+///
+/// let corruption = Corruption::new().bit_flip( 0.001 ).truncate( 0.01 );
+///
+/// let (a, b) = EndpointBuilder::new()
+///
+///    .a_to_b( DirectionConfig::default().corrupt( corruption, Dictator::new_seed() ) )
+///    .build()
+/// ;
+/// ```
+//
+#[ derive( Debug, Clone, Default, PartialEq ) ]
+//
+pub struct Corruption
+{
+	bit_flip  : f64,
+	substitute: f64,
+	truncate  : f64,
+}
+
+
+impl Corruption
+{
+	/// Nothing is corrupted.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// The probability, per byte, that one of its bits is flipped.
+	///
+	/// # Panics
+	///
+	/// When `probability` is not between 0 and 1.
+	//
+	pub fn bit_flip( mut self, probability: f64 ) -> Self
+	{
+		self.bit_flip = check_probability( probability );
+		self
+	}
+
+
+	/// The probability, per byte, that it is replaced by another value.
+	///
+	/// # Panics
+	///
+	/// When `probability` is not between 0 and 1.
+	//
+	pub fn substitute( mut self, probability: f64 ) -> Self
+	{
+		self.substitute = check_probability( probability );
+		self
+	}
+
+
+	/// The probability, per write, that the end of the data written is lost. The writer is told that all
+	/// data was written.
+	///
+	/// # Panics
+	///
+	/// When `probability` is not between 0 and 1.
+	//
+	pub fn truncate( mut self, probability: f64 ) -> Self
+	{
+		self.truncate = check_probability( probability );
+		self
+	}
+}
+
+
+
+/// Corrupts the data written in one direction.
+//
+#[ derive( Debug ) ]
+//
+pub(crate) struct Corruptor
+{
+	config  : Corruption ,
+	dictator: Dictator   ,
+	label   : String     ,
+	offset  : u64        ,
+}
+
+
+impl Corruptor
+{
+	/// `label` identifies the direction in the logs.
+	//
+	pub(crate) fn new( config: Corruption, seed: u64, label: String ) -> Self
+	{
+		log::debug!( "{}: corrupting data with {:?}, seed: {}", label, config, seed );
+
+		Self { config, dictator: Dictator::new( seed ), label, offset: 0 }
+	}
+
+
+	/// Corrupt `data`, which is the next chunk written in this direction. It might be truncated.
+	//
+	pub(crate) fn corrupt( &mut self, data: &mut Vec<u8> )
+	{
+		for (i, byte) in data.iter_mut().enumerate()
+		{
+			let offset = self.offset + i as u64;
+
+			if self.dictator.please( "flip bit", self.config.bit_flip )
+			{
+				let bit  = self.dictator.pick( "bit to flip", 0..8u8 );
+				let old  = *byte;
+				*byte   ^= 1 << bit;
+
+				log::debug!( "{}: flipped bit {} at offset {}: {:#04x} -> {:#04x}", self.label, bit, offset, old, byte );
+			}
+
+			if self.dictator.please( "substitute byte", self.config.substitute )
+			{
+				// Make sure the byte actually changes.
+				//
+				let old = *byte;
+				*byte   = old.wrapping_add( self.dictator.pick( "substitute", 1..256u16 ) as u8 );
+
+				log::debug!( "{}: substituted byte at offset {}: {:#04x} -> {:#04x}", self.label, offset, old, byte );
+			}
+		}
+
+		self.offset += data.len() as u64;
+
+		if !data.is_empty() && self.dictator.please( "truncate", self.config.truncate )
+		{
+			let keep = self.dictator.pick( "bytes to keep", 0..data.len() );
+
+			log::debug!( "{}: truncated write, lost {} bytes at offset {}", self.label, data.len() - keep, self.offset - ( data.len() - keep ) as u64 );
+
+			data.truncate( keep );
+		}
+	}
+}
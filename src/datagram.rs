@@ -0,0 +1,471 @@
+use crate::{ import::*, RingBuffer, ReadHalf, WriteHalf };
+use futures::{ SinkExt, StreamExt };
+
+#[ cfg( feature = "sketchy" ) ] use crate::{ Dictator, dictator::check_probability };
+
+
+/// A message oriented connection, like UDP, WebSocket or QUIC datagrams. Contrary to [`Endpoint`](crate::Endpoint),
+/// which is a byte stream, every message sent is received as a whole.
+///
+/// Each direction has a bounded queue of messages. When it's full, sending is pending until the remote endpoint
+/// receives a message. Messages larger than the maximum datagram size are rejected with `io::ErrorKind::InvalidInput`.
+///
+/// Implements `Sink<Vec<u8>>` and `Stream<Item = Vec<u8>>`. The stream ends when the remote endpoint is closed or
+/// dropped and all messages have been received. Sending after the remote endpoint has been dropped fails with
+/// `io::ErrorKind::BrokenPipe`.
+///
+/// The storage of each direction is a [`RingBuffer`] holding the bytes of the messages and one holding their
+/// lengths.
+///
+/// With the `sketchy` feature, [`DatagramEndpoint::with_faults`] turns it into an unreliable link that drops,
+/// duplicates and reorders messages.
+///
+/// ```
+/// use futures_ringbuf::DatagramEndpoint;
+/// use futures::executor::block_on;
+///
+/// let (mut a, mut b) = DatagramEndpoint::pair( 8, 1500 );
+///
+/// block_on( async
+/// {
+///    a.send( b"hello" ).await.expect( "send" );
+///    a.send( b"world" ).await.expect( "send" );
+///
+///    assert_eq!( b.recv().await, Some( b"hello".to_vec() ) );
+///    assert_eq!( b.recv().await, Some( b"world".to_vec() ) );
+/// });
+/// ```
+//
+#[ derive( Debug ) ]
+//
+pub struct DatagramEndpoint
+{
+	max_size : usize            ,
+	send_len : WriteHalf<usize> ,
+	send_data: WriteHalf<u8>    ,
+	recv_len : ReadHalf <usize> ,
+	recv_data: ReadHalf <u8>    ,
+
+	#[ cfg( feature = "sketchy" ) ]
+	//
+	faults: Option<Faults>,
+}
+
+
+impl DatagramEndpoint
+{
+	/// Create a pair of connected endpoints. In each direction, up to `queue` messages of at most `max_size`
+	/// bytes can be waiting to be received.
+	///
+	/// # Panics
+	///
+	/// If `queue` is zero.
+	//
+	pub fn pair( queue: usize, max_size: usize ) -> (DatagramEndpoint, DatagramEndpoint)
+	{
+		assert!( queue > 0, "futures_ringbuf: the queue of a DatagramEndpoint must hold at least one message" );
+
+		let (ab_len, ab_data) = direction( queue, max_size );
+		let (ba_len, ba_data) = direction( queue, max_size );
+
+		(
+			Self::new( max_size, ab_len.1, ab_data.1, ba_len.0, ba_data.0 ),
+			Self::new( max_size, ba_len.1, ba_data.1, ab_len.0, ab_data.0 ),
+		)
+	}
+
+
+	fn new
+	(
+		max_size : usize            ,
+		send_len : WriteHalf<usize> ,
+		send_data: WriteHalf<u8>    ,
+		recv_len : ReadHalf <usize> ,
+		recv_data: ReadHalf <u8>    ,
+	)
+		-> Self
+	{
+		Self
+		{
+			max_size  ,
+			send_len  ,
+			send_data ,
+			recv_len  ,
+			recv_data ,
+
+			#[ cfg( feature = "sketchy" ) ]
+			//
+			faults: None,
+		}
+	}
+
+
+	/// Make the messages sent by this endpoint unreliable. A [`Dictator`] created with `seed` decides which
+	/// messages are dropped, duplicated or reordered, so a failing test can be reproduced with the seed it logs.
+	///
+	/// A reordered message is held back and delivered after the next one, or when the endpoint is flushed,
+	/// closed or dropped. A duplicate is only delivered if there is place for it in the queue.
+	//
+	#[ cfg( feature = "sketchy" ) ]
+	//
+	pub fn with_faults( mut self, config: DatagramFaults, seed: u64 ) -> Self
+	{
+		self.faults = Some( Faults { config, dictator: Dictator::new( seed ), held: None } );
+		self
+	}
+
+
+	/// The maximum size of a message in bytes.
+	//
+	pub fn max_size( &self ) -> usize
+	{
+		self.max_size
+	}
+
+
+	/// Send a message, waiting for place in the queue if needed.
+	///
+	/// # Errors
+	///
+	/// - `io::ErrorKind::InvalidInput` when the message is larger than [`DatagramEndpoint::max_size`].
+	/// - `io::ErrorKind::NotConnected` when this endpoint has been closed.
+	/// - `io::ErrorKind::BrokenPipe` when the remote endpoint has been dropped.
+	//
+	pub async fn send( &mut self, msg: &[u8] ) -> io::Result<()>
+	{
+		self.check_size( msg.len() )?;
+
+		futures::future::poll_fn( |cx| self.poll_send_ready( cx ) ).await?;
+
+		self.send_now( msg )
+	}
+
+
+	/// Receive the next message. Returns `None` when the remote endpoint has been closed or dropped and
+	/// all messages have been received.
+	//
+	pub async fn recv( &mut self ) -> Option< Vec<u8> >
+	{
+		self.next().await
+	}
+
+
+	/// Wait for place in the queue to send a message.
+	//
+	fn poll_send_ready( &mut self, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.send_len.poll_ready_unpin( cx )
+	}
+
+
+	fn check_size( &self, len: usize ) -> io::Result<()>
+	{
+		if len > self.max_size
+		{
+			let msg = format!( "futures_ringbuf::DatagramEndpoint: message of {} bytes is larger than the maximum of {}", len, self.max_size );
+
+			return Err( io::Error::new( io::ErrorKind::InvalidInput, msg ) );
+		}
+
+		Ok(())
+	}
+
+
+	/// Send a message that has place in the queue, letting the faults, if any, decide what happens to it.
+	//
+	fn send_now( &mut self, msg: &[u8] ) -> io::Result<()>
+	{
+		#[ cfg( feature = "sketchy" ) ]
+		//
+		if let Some(mut faults) = self.faults.take()
+		{
+			let result  = faults.send( self, msg );
+			self.faults = Some( faults );
+
+			return result;
+		}
+
+		self.push( msg )
+	}
+
+
+	/// Put a message in the queue. The caller must have checked there is place for it.
+	//
+	fn push( &mut self, msg: &[u8] ) -> io::Result<()>
+	{
+		// The data goes first, so the reader never sees a length without the data.
+		//
+		let wrote = self.send_data.write_now( msg )?;
+
+		debug_assert_eq!( wrote, msg.len(), "the data buffer has place for a message when the queue has" );
+
+		self.send_len.start_send_unpin( msg.len() )
+	}
+}
+
+
+/// Delivers a message held back to be reordered, if there is place for it in the queue.
+//
+#[ cfg( feature = "sketchy" ) ]
+//
+impl Drop for DatagramEndpoint
+{
+	fn drop( &mut self )
+	{
+		let held = match self.faults.as_mut().and_then( |f| f.held.take() )
+		{
+			Some(held) => held,
+			None       => return,
+		};
+
+		if self.send_len.is_full()
+		{
+			log::warn!( "DatagramEndpoint: dropped with a reordered message of {} bytes and no place to deliver it, the message is lost", held.len() );
+			return;
+		}
+
+		log::debug!( "DatagramEndpoint: delivering reordered message of {} bytes on drop", held.len() );
+
+		// If the remote endpoint is gone, nobody would receive it anyway.
+		//
+		let _ = self.push( &held );
+	}
+}
+
+
+/// The ringbuffers for one direction. As long as there is place in the queue of lengths, there is place
+/// for a message in the data buffer. It has place for an extra message because the reader takes the length
+/// before the data.
+//
+#[ allow( clippy::type_complexity ) ]
+//
+fn direction( queue: usize, max_size: usize ) -> ( (ReadHalf<usize>, WriteHalf<usize>), (ReadHalf<u8>, WriteHalf<u8>) )
+{
+	let lengths = RingBuffer::<usize>::new( queue );
+	let data    = RingBuffer::<u8>   ::new( ( queue + 1 ) * max_size.max( 1 ) );
+
+	( lengths.split(), data.split() )
+}
+
+
+
+impl Stream for DatagramEndpoint
+{
+	type Item = Vec<u8>;
+
+	/// Pending while there is no message. Returns `None` when the remote endpoint has been closed or
+	/// dropped and all messages have been received.
+	//
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		let len = match ready!( self.recv_len.poll_next_unpin( cx ) )
+		{
+			Some(len) => len,
+			None      => return Poll::Ready( None ),
+		};
+
+		let mut msg = vec![ 0u8; len ];
+		let read    = self.recv_data.read_now( &mut msg );
+
+		debug_assert_eq!( read, len, "the data is in the buffer before the length" );
+
+		Poll::Ready( Some( msg ) )
+	}
+}
+
+
+
+impl Sink< Vec<u8> > for DatagramEndpoint
+{
+	type Error = io::Error;
+
+
+	/// Pending while the queue is full.
+	//
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.poll_send_ready( cx )
+	}
+
+
+	/// Returns `io::ErrorKind::InvalidInput` if the message is too large, and `io::ErrorKind::WouldBlock`
+	/// if you didn't wait for `poll_ready`.
+	//
+	fn start_send( mut self: Pin<&mut Self>, msg: Vec<u8> ) -> io::Result<()>
+	{
+		self.check_size( msg.len() )?;
+
+		if self.send_len.is_full()
+		{
+			return Err( io::ErrorKind::WouldBlock.into() );
+		}
+
+		self.send_now( &msg )
+	}
+
+
+	/// Messages are delivered immediately, so there is nothing to flush, except for a message held back
+	/// to be reordered.
+	//
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		#[ cfg( feature = "sketchy" ) ]
+		//
+		if let Some(held) = self.faults.as_ref().and_then( |f| f.held.as_ref() ).map( Vec::len )
+		{
+			ready!( self.poll_send_ready( cx ) )?;
+
+			log::debug!( "DatagramEndpoint: delivering reordered message of {} bytes", held );
+
+			let msg = self.faults.as_mut().and_then( |f| f.held.take() ).unwrap_or_default();
+			self.push( &msg )?;
+		}
+
+		self.send_len.poll_flush_unpin( cx )
+	}
+
+
+	/// Close this endpoint. The remote endpoint can receive the messages in the queue and then sees the
+	/// end of the stream.
+	//
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		ready!( self.as_mut().poll_flush( cx ) )?;
+		ready!( AsyncWrite::poll_close( Pin::new( &mut self.send_data ), cx ) )?;
+
+		self.send_len.poll_close_unpin( cx )
+	}
+}
+
+
+
+/// The probabilities of faults on the messages sent by a [`DatagramEndpoint`]. Set it with
+/// [`DatagramEndpoint::with_faults`]. By default, nothing goes wrong.
+///
+/// ```
+/// use futures_ringbuf::{ DatagramEndpoint, DatagramFaults, Dictator };
+///
+/// let (a, b) = DatagramEndpoint::pair( 16, 1500 );
+///
+/// let faults = DatagramFaults::new().loss( 0.1 ).duplicate( 0.05 ).reorder( 0.1 );
+/// let a      = a.with_faults( faults, Dictator::new_seed() );
+/// ```
+//
+#[ cfg( feature = "sketchy" ) ]
+//
+#[ derive( Debug, Clone, Default, PartialEq ) ]
+//
+pub struct DatagramFaults
+{
+	loss     : f64,
+	duplicate: f64,
+	reorder  : f64,
+}
+
+
+#[ cfg( feature = "sketchy" ) ]
+//
+impl DatagramFaults
+{
+	/// No faults.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// The probability that a message is lost.
+	///
+	/// # Panics
+	///
+	/// When `probability` is not between 0 and 1.
+	//
+	pub fn loss( mut self, probability: f64 ) -> Self
+	{
+		self.loss = check_probability( probability );
+		self
+	}
+
+
+	/// The probability that a message is delivered twice.
+	///
+	/// # Panics
+	///
+	/// When `probability` is not between 0 and 1.
+	//
+	pub fn duplicate( mut self, probability: f64 ) -> Self
+	{
+		self.duplicate = check_probability( probability );
+		self
+	}
+
+
+	/// The probability that a message is delivered after the next one. The last message can only be
+	/// delivered when the endpoint is flushed, closed or dropped, and is lost if the queue is full by then.
+	///
+	/// # Panics
+	///
+	/// When `probability` is not between 0 and 1.
+	//
+	pub fn reorder( mut self, probability: f64 ) -> Self
+	{
+		self.reorder = check_probability( probability );
+		self
+	}
+}
+
+
+/// The state of an unreliable link.
+//
+#[ cfg( feature = "sketchy" ) ]
+//
+#[ derive( Debug ) ]
+//
+struct Faults
+{
+	config  : DatagramFaults    ,
+	dictator: Dictator          ,
+	held    : Option< Vec<u8> > ,
+}
+
+
+#[ cfg( feature = "sketchy" ) ]
+//
+impl Faults
+{
+	fn send( &mut self, ep: &mut DatagramEndpoint, msg: &[u8] ) -> io::Result<()>
+	{
+		if self.dictator.please( "drop datagram", self.config.loss )
+		{
+			log::debug!( "DatagramEndpoint: dropping message of {} bytes", msg.len() );
+			return Ok(());
+		}
+
+		if self.held.is_none() && self.dictator.please( "reorder datagram", self.config.reorder )
+		{
+			log::debug!( "DatagramEndpoint: holding back message of {} bytes", msg.len() );
+			self.held = Some( msg.to_vec() );
+			return Ok(());
+		}
+
+		ep.push( msg )?;
+
+		if self.dictator.please( "duplicate datagram", self.config.duplicate ) && !ep.send_len.is_full()
+		{
+			log::debug!( "DatagramEndpoint: duplicating message of {} bytes", msg.len() );
+			ep.push( msg )?;
+		}
+
+		if !ep.send_len.is_full()
+		{
+			if let Some(held) = self.held.take()
+			{
+				log::debug!( "DatagramEndpoint: delivering reordered message of {} bytes", held.len() );
+				ep.push( &held )?;
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -75,6 +75,20 @@ impl Dictator
 }
 
 
+/// Validate a probability passed to the configuration of one of the types driven by a `Dictator`.
+///
+/// # Panics
+///
+/// When `probability` is not between 0 and 1.
+//
+pub(crate) fn check_probability( probability: f64 ) -> f64
+{
+	assert!( ( 0.0..=1.0 ).contains( &probability ), "futures_ringbuf: probability must be between 0 and 1, got {}", probability );
+
+	probability
+}
+
+
 #[ cfg(test) ]
 //
 mod tests
@@ -1,14 +1,15 @@
-use crate::{ import::*, RingBuffer };
+use crate::{ import::*, ReadHalf, WriteHalf, EndpointBuilder, DirectionConfig, split::Shared };
 
 /// Represents a network endpoint. This is for duplex connection mocking. Each direction has a separate
 /// ringbuffer and one buffer's readhalf is connected to the other buffer's writehalf in order to simulate
 /// a duplex connection.
 ///
 /// The main way to create this is to call `Endpoint::pair` which returns a tuple of endpoints, and which
-/// let's you specify the buffer size for each direction.
+/// let's you specify the buffer size for each direction. For more options, use [`EndpointBuilder`].
 ///
 /// Endpoint implements AsyncRead/AsyncWrite so you can feed it to interfaces that need those combined in
-/// a single type.
+/// a single type. It also implements AsyncBufRead, lending out the data in the ringbuffer directly, so
+/// `read_line`/`read_until` don't need an extra `BufReader`.
 ///
 /// By setting the buffer size precisely, one can simulate back pressure. Endpoint will return Pending on writes
 /// when full and on reads when empty.
@@ -19,13 +20,29 @@ use crate::{ import::*, RingBuffer };
 ///
 /// If the remote endpoint is pending on a read, the task will be woken up when calling `close` or dropping
 /// this endpoint.
+///
+/// When an endpoint is dropped, writes on the remote endpoint will return [`std::io::ErrorKind::BrokenPipe`],
+/// like on a real network connection or pipe. A pending writer on the remote endpoint will be woken up.
+///
+/// With [`EndpointBuilder`] you can simulate latency and limited bandwidth on each direction, and record
+/// all traffic with a [`Recorder`](crate::Recorder).
+///
+/// To simulate half closed connections, use [`Endpoint::shutdown_direction`]. To simulate an abrupt connection
+/// loss, use [`Endpoint::reset`] or a [`ResetHandle`].
+///
+/// Each direction is a [`RingBuffer`](crate::RingBuffer) split in a [`ReadHalf`] and a [`WriteHalf`]. These
+/// don't share any lock, so with [`Endpoint::into_split`] you can move the reading and the writing side to
+/// different tasks.
 //
 #[ derive( Debug ) ]
 //
 pub struct Endpoint
 {
-	writer: WriteHalf< RingBuffer<u8> >,
-	reader: ReadHalf < RingBuffer<u8> >,
+	pub(crate) name      : String        ,
+	pub(crate) local_addr: SocketAddr    ,
+	pub(crate) peer_addr : SocketAddr    ,
+	pub(crate) writer    : WriteHalf<u8> ,
+	pub(crate) reader    : ReadHalf <u8> ,
 }
 
 
@@ -36,16 +53,115 @@ impl Endpoint
 	//
 	pub fn pair( a_buf: usize, b_buf: usize ) -> (Endpoint, Endpoint)
 	{
-		let ab_buf = RingBuffer::<u8>::new( a_buf );
-		let ba_buf = RingBuffer::<u8>::new( b_buf );
+		EndpointBuilder::new()
+
+			.a_to_b( DirectionConfig::new( a_buf ) )
+			.b_to_a( DirectionConfig::new( b_buf ) )
+			.build()
+	}
+
+
+	/// The name of this endpoint, as set by [`EndpointBuilder`]. Endpoints created with `pair` are called
+	/// "a" and "b".
+	//
+	pub fn name( &self ) -> &str
+	{
+		&self.name
+	}
+
+
+	/// The address of this endpoint, as set by [`EndpointBuilder`]. This never fails, it returns a
+	/// result to have the same signature as [`std::net::TcpStream::local_addr`].
+	//
+	pub fn local_addr( &self ) -> io::Result<SocketAddr>
+	{
+		Ok( self.local_addr )
+	}
+
 
-		let (ab_reader, ab_writer) = ab_buf.split();
-		let (ba_reader, ba_writer) = ba_buf.split();
+	/// The address of the remote endpoint, as set by [`EndpointBuilder`]. This never fails, it returns a
+	/// result to have the same signature as [`std::net::TcpStream::peer_addr`].
+	//
+	pub fn peer_addr( &self ) -> io::Result<SocketAddr>
+	{
+		Ok( self.peer_addr )
+	}
+
+
+	/// Shut down the reading side, the writing side or both, like [`std::net::TcpStream::shutdown`].
+	///
+	/// - `Shutdown::Write` is the same as `close`: further writes on this endpoint return
+	///   [`std::io::ErrorKind::NotConnected`] and the remote endpoint can read the remaining data
+	///   and then gets end of stream.
+	/// - `Shutdown::Read` discards any data buffered for this endpoint. Further reads on this endpoint
+	///   return `Ok(0)` and writes on the remote endpoint return [`std::io::ErrorKind::BrokenPipe`].
+	///   A pending writer on the remote endpoint will be woken up.
+	/// - `Shutdown::Both` does both.
+	///
+	/// The other direction keeps working normally, so you can test protocols that rely on half closed
+	/// connections.
+	///
+	/// It's not called `shutdown` so it doesn't shadow `shutdown` from tokio's `AsyncWriteExt`.
+	///
+	/// # Errors
+	///
+	/// `io::ErrorKind::ConnectionReset` when the connection has been reset.
+	//
+	pub fn shutdown_direction( &mut self, how: Shutdown ) -> io::Result<()>
+	{
+		log::trace!( "{}: shutdown_direction({:?})", self.name, how );
+
+		self.reader.shared().check_reset()?;
+
+		if let Shutdown::Read | Shutdown::Both = how
+		{
+			self.reader.shutdown();
+		}
+
+		if let Shutdown::Write | Shutdown::Both = how
+		{
+			self.writer.close_writer();
+		}
+
+		Ok(())
+	}
+
+
+	/// Abort the connection, like a TCP reset. Every pending and subsequent read, write, flush or close on both
+	/// endpoints fails with [`std::io::ErrorKind::ConnectionReset`] and pending tasks on both endpoints are
+	/// woken up.
+	///
+	/// Data buffered in either direction can no longer be read. It is discarded the next time the reading side
+	/// of that direction is polled, until then it still counts in the length and remaining capacity of the
+	/// buffer.
+	//
+	pub fn reset( &self )
+	{
+		log::trace!( "{}: reset()", self.name );
 
-		(
-			Endpoint{ writer: ab_writer, reader: ba_reader },
-			Endpoint{ writer: ba_writer, reader: ab_reader },
-		)
+		self.reset_handle().reset();
+	}
+
+
+	/// Get a handle that can reset this connection from elsewhere, eg. to simulate the remote peer or a
+	/// network failure aborting the connection while the endpoints are owned by the code under test.
+	/// The handle keeps working if the endpoint is split with [`Endpoint::into_split`].
+	//
+	pub fn reset_handle( &self ) -> ResetHandle
+	{
+		ResetHandle
+		{
+			directions: [ self.reader.shared().clone(), self.writer.shared().clone() ],
+		}
+	}
+
+
+	/// Split the endpoint in an owned reading and writing half, so you can move them to different tasks.
+	/// Dropping the writing half will close it, like dropping the endpoint would.
+	//
+	pub fn into_split( self ) -> (ReadHalf<u8>, WriteHalf<u8>)
+	{
+		(self.reader, self.writer)
 	}
 }
 
@@ -57,10 +173,32 @@ impl AsyncRead for Endpoint
 	{
 		let poll = Pin::new(&mut self.reader).poll_read(cx, buf);
 
-		log::trace!("poll_read() => {:?}", poll);
+		log::trace!( "{}: poll_read() => {:?}", self.name, poll );
+
+		poll
+	}
+}
+
+
+
+impl AsyncBufRead for Endpoint
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		let this = self.get_mut();
+		let poll = Pin::new( &mut this.reader ).poll_fill_buf( cx );
+
+		log::trace!( "{}: poll_fill_buf() => {:?}", this.name, poll );
 
 		poll
 	}
+
+	fn consume( mut self: Pin<&mut Self>, amt: usize )
+	{
+		log::trace!( "{}: consume({})", self.name, amt );
+
+		Pin::new( &mut self.reader ).consume( amt )
+	}
 }
 
 
@@ -71,16 +209,16 @@ impl AsyncWrite for Endpoint
 	{
 		let poll = Pin::new(&mut self.writer).poll_write(cx, buf);
 
-		log::trace!("poll_write() => {:?}", poll);
+		log::trace!( "{}: poll_write() => {:?}", self.name, poll );
 
 		poll
 	}
 
 	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll< io::Result<()> >
 	{
-		let poll = Pin::new(&mut self.writer).poll_flush(cx);
+		let poll = AsyncWrite::poll_flush( Pin::new( &mut self.writer ), cx );
 
-		log::trace!("poll_flush() => {:?}", poll);
+		log::trace!( "{}: poll_flush() => {:?}", self.name, poll );
 
 		poll
 
@@ -88,27 +226,37 @@ impl AsyncWrite for Endpoint
 
 	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
 	{
-		let poll = Pin::new(&mut self.writer).poll_close(cx);
+		let poll = AsyncWrite::poll_close( Pin::new( &mut self.writer ), cx );
 
-		log::trace!("poll_close() => {:?}", poll);
+		log::trace!( "{}: poll_close() => {:?}", self.name, poll );
 
 		poll
 	}
 }
 
 
-/// Makes sure that the reader of the other end is notified (woken up if pending) of the connection closure.
+
+/// Resets a connection created with [`Endpoint::pair`] when you call [`ResetHandle::reset`], with the same
+/// effect as [`Endpoint::reset`]. Obtained from [`Endpoint::reset_handle`]. Cloning this gives you another
+/// handle to the same connection.
+//
+#[ derive( Debug, Clone ) ]
 //
-impl Drop for Endpoint
+pub struct ResetHandle
 {
-	fn drop( &mut self )
-	{
-		// Closing the ringbuffer is not an async operation. It always returns immediately,
-		// so we just ignore the return value of poll_close.
-		//
-		let waker  = noop_waker();
-		let mut cx = Context::from_waker( &waker );
+	directions: [ Arc<Shared>; 2 ],
+}
 
-		let _ = Pin::new( self ).poll_close( &mut cx);
+
+impl ResetHandle
+{
+	/// Abort the connection. See [`Endpoint::reset`].
+	//
+	pub fn reset( &self )
+	{
+		for direction in &self.directions
+		{
+			direction.reset();
+		}
 	}
 }
@@ -0,0 +1,152 @@
+use crate::{ import::*, Clock };
+
+
+/// Keeps track of when data written to a ringbuffer may be read, to simulate latency. For every write,
+/// the writer records the total amount of data written so far and the time at which it becomes readable.
+/// The reader only allows reading up to the last entry that has matured.
+///
+/// The schedule is a ringbuffer itself, so this stays lock free. Every entry covers at least one element
+/// that is still in the data buffer, so it never needs more room than the data buffer.
+//
+pub(crate) fn latency( capacity: usize, delay: Duration, clock: Arc<dyn Clock> ) -> (LatencyReader, LatencyWriter)
+{
+	let (producer, consumer) = SyncRingBuffer::new( capacity ).split();
+
+	(
+		LatencyReader { schedule: consumer, clock: clock.clone(), read   : 0, readable: 0, timer: None } ,
+		LatencyWriter { schedule: producer, clock               , written: 0, delay       } ,
+	)
+}
+
+
+/// The reading side of the latency schedule.
+//
+pub(crate) struct LatencyReader
+{
+	schedule: Consumer<(u64, Duration)> ,
+	clock   : Arc<dyn Clock>            ,
+	read    : u64                       ,
+	readable: u64                       ,
+
+	// The wake up we asked the clock for, so we don't ask again every time we are polled.
+	//
+	timer: Option<(Duration, Waker)>,
+}
+
+
+/// The writing side of the latency schedule.
+//
+pub(crate) struct LatencyWriter
+{
+	schedule: Producer<(u64, Duration)> ,
+	clock   : Arc<dyn Clock>            ,
+	written : u64                       ,
+	delay   : Duration                  ,
+}
+
+
+impl LatencyReader
+{
+	/// How many elements can be read right now.
+	//
+	pub(crate) fn available( &mut self ) -> usize
+	{
+		let now = self.clock.now();
+
+		loop
+		{
+			let next = self.schedule.iter().next().copied();
+
+			match next
+			{
+				Some( (end, at) ) if at <= now =>
+				{
+					self.readable = end;
+					self.schedule.skip( 1 );
+				}
+
+				_ => break,
+			}
+		}
+
+		( self.readable - self.read ) as usize
+	}
+
+
+	/// Record that `amount` elements have been read.
+	//
+	pub(crate) fn read( &mut self, amount: usize )
+	{
+		self.read += amount as u64;
+	}
+
+
+	/// If there is data that hasn't matured yet, make sure `waker` gets woken up when it does.
+	//
+	pub(crate) fn wake_when_ready( &mut self, waker: &Waker )
+	{
+		let at = match self.schedule.iter().next()
+		{
+			Some( &(_, at) ) => at,
+			None             => return,
+		};
+
+		if let Some( (deadline, registered) ) = &self.timer
+		{
+			if *deadline == at && registered.will_wake( waker )
+			{
+				return;
+			}
+		}
+
+		self.clock.wake_at( at, waker.clone() );
+		self.timer = Some(( at, waker.clone() ));
+	}
+}
+
+
+impl LatencyWriter
+{
+	/// Record that `amount` elements have been written right now.
+	//
+	pub(crate) fn wrote( &mut self, amount: usize )
+	{
+		if amount == 0 { return }
+
+		self.written += amount as u64;
+
+		let at = self.clock.now() + self.delay;
+
+		// This can't fail, see the explanation on `latency`.
+		//
+		let _ = self.schedule.push( (self.written, at) );
+	}
+}
+
+
+impl fmt::Debug for LatencyReader
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "LatencyReader" )
+
+			.field( "clock"   , &self.clock    )
+			.field( "read"    , &self.read     )
+			.field( "readable", &self.readable )
+			.finish()
+	}
+}
+
+
+impl fmt::Debug for LatencyWriter
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "LatencyWriter" )
+
+			.field( "clock"  , &self.clock   )
+			.field( "written", &self.written )
+			.field( "delay"  , &self.delay   )
+			.finish()
+	}
+}
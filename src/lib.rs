@@ -24,16 +24,50 @@
 mod ring_buffer ;
 pub use self::ring_buffer::* ;
 
-mod async_read  ;
-mod async_write ;
-mod endpoint    ;
+mod async_read     ;
+mod async_buf_read ;
+mod async_write    ;
+mod stream         ;
+mod sink           ;
+mod split          ;
+mod endpoint       ;
+mod builder        ;
+mod clock          ;
+mod latency        ;
+mod throttle       ;
+mod mock           ;
+mod recorder       ;
+mod replay         ;
+mod pcap           ;
+mod network        ;
+mod datagram       ;
+mod waker_audit    ;
 
-pub use endpoint::*;
+pub use split::{ ReadHalf, WriteHalf }             ;
+pub use endpoint::*                               ;
+pub use builder::*                                ;
+pub use clock::*                                  ;
+pub use mock::*                                   ;
+pub use recorder::{ Recorder, Record, Transcript } ;
+pub use replay::Replay                            ;
+pub use network::{ MockNetwork, MockListener }     ;
+pub use datagram::*                               ;
+pub use waker_audit::*                            ;
+
+/// Re-exported for use with [`Endpoint::shutdown_direction`].
+//
+pub use std::net::Shutdown;
+
+#[ cfg( feature = "tokio"   ) ] mod tokio_io        ;
 
 #[ cfg( feature = "sketchy" ) ] mod dictator        ;
 #[ cfg( feature = "sketchy" ) ] mod sketchy         ;
-#[ cfg( feature = "sketchy" ) ] pub use dictator::* ;
-#[ cfg( feature = "sketchy" ) ] pub use sketchy::*  ;
+#[ cfg( feature = "sketchy" ) ] mod sketchy_config  ;
+#[ cfg( feature = "sketchy" ) ] mod corruption      ;
+#[ cfg( feature = "sketchy" ) ] pub use dictator::*            ;
+#[ cfg( feature = "sketchy" ) ] pub use sketchy::*             ;
+#[ cfg( feature = "sketchy" ) ] pub use sketchy_config::*      ;
+#[ cfg( feature = "sketchy" ) ] pub use corruption::Corruption ;
 
 
 // External dependencies
@@ -42,12 +76,25 @@ mod import
 {
 	pub(crate) use
 	{
-		std         :: { fmt, task::Waker                      } ,
-		ringbuf     :: { HeapRb as SyncRingBuffer              } ,
-		futures     :: { AsyncRead, AsyncWrite, AsyncReadExt   } ,
-		futures::io :: { ReadHalf, WriteHalf                   } ,
-		futures     :: { task::noop_waker                      } ,
-		std         :: { io, pin::Pin, task::{ Context, Poll } } ,
+		std         :: { fmt, task::Waker, net::Shutdown, time::Duration } ,
+		std         :: { sync::{ Arc, atomic::{ AtomicBool, Ordering } } } ,
+		std         :: { sync::{ Mutex, PoisonError }                  } ,
+		std         :: { net::{ SocketAddr, SocketAddrV4, Ipv4Addr }   } ,
+		ringbuf     :: { HeapRb as SyncRingBuffer                      } ,
+		crate       :: { Producer, Consumer                            } ,
+		futures     :: { AsyncRead, AsyncBufRead, AsyncWrite           } ,
+		futures     :: { Stream, Sink                                  } ,
+		futures     :: { ready, task::{ AtomicWaker, ArcWake }          } ,
+		std         :: { future::Future                                } ,
+		std         :: { io, pin::Pin, task::{ Context, Poll }         } ,
+	};
+
+
+	#[ cfg( not( target_arch = "wasm32" ) ) ]
+	//
+	pub(crate) use
+	{
+		std :: { time::Instant, sync::{ Condvar, Once, Weak } } ,
 	};
 
 
@@ -55,7 +102,7 @@ mod import
 	//
 	pub(crate) use
 	{
-		futures           :: { AsyncWriteExt             } ,
+		futures           :: { AsyncReadExt, AsyncWriteExt } ,
 		pretty_assertions :: { assert_eq                 } ,
 		futures           :: { executor::block_on        } ,
 		futures_test      :: { task::{ new_count_waker } } ,
@@ -0,0 +1,453 @@
+use crate::{ import::*, RingBuffer, Clock, builder::default_clock };
+use std::collections::VecDeque;
+
+
+/// One step of the script of a [`MockStream`].
+//
+#[ derive( Debug, Clone ) ]
+//
+enum Action
+{
+	Read      ( Vec<u8>       ),
+	Write     ( Vec<u8>       ),
+	Wait      ( Duration      ),
+	ReadError ( io::ErrorKind ),
+	WriteError( io::ErrorKind ),
+}
+
+
+impl Action
+{
+	fn is_write( &self ) -> bool
+	{
+		matches!( self, Action::Write(_) | Action::WriteError(_) )
+	}
+}
+
+
+
+/// Build a [`MockStream`] by describing what the code under test should see when reading and what it should
+/// write. Reads and writes are independent of each other, except that:
+///
+/// - a read waits until all writes scripted before it have been done,
+/// - a wait blocks both reading and writing until it has elapsed.
+///
+/// ```
+/// use futures_ringbuf::MockStreamBuilder;
+/// use futures::{ AsyncReadExt, AsyncWriteExt, executor::block_on };
+///
+/// let mut mock = MockStreamBuilder::new()
+///
+///    .write( b"ping" )
+///    .read ( b"pong" )
+///    .build()
+/// ;
+///
+/// block_on( async
+/// {
+///    let mut buf = [0u8; 4];
+///
+///    mock.write_all( b"ping" ).await.expect( "write" );
+///    mock.read_exact( &mut buf ).await.expect( "read" );
+///
+///    assert_eq!( &buf, b"pong" );
+/// });
+/// ```
+//
+#[ derive( Debug, Clone, Default ) ]
+//
+pub struct MockStreamBuilder
+{
+	actions: VecDeque<Action>         ,
+	clock  : Option< Arc<dyn Clock> > ,
+}
+
+
+impl MockStreamBuilder
+{
+	/// Create a builder with an empty script. A mock stream built from it will return end of stream
+	/// when read from and panic when written to.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// The code under test will read `data`. It might take several calls to `poll_read` if the buffer
+	/// passed in is smaller than `data`.
+	//
+	pub fn read( mut self, data: &[u8] ) -> Self
+	{
+		if !data.is_empty()
+		{
+			self.actions.push_back( Action::Read( data.to_vec() ) );
+		}
+
+		self
+	}
+
+
+	/// The code under test must write `data`. It can do so in several calls to `poll_write`. If it writes
+	/// something else, the mock stream panics and shows the difference.
+	//
+	pub fn write( mut self, data: &[u8] ) -> Self
+	{
+		if !data.is_empty()
+		{
+			self.actions.push_back( Action::Write( data.to_vec() ) );
+		}
+
+		self
+	}
+
+
+	/// Both reading and writing will be pending for `duration`, according to the clock set with
+	/// [`MockStreamBuilder::clock`].
+	//
+	pub fn wait( mut self, duration: Duration ) -> Self
+	{
+		self.actions.push_back( Action::Wait( duration ) );
+		self
+	}
+
+
+	/// The next read will fail with an error of `kind`.
+	//
+	pub fn read_error( mut self, kind: io::ErrorKind ) -> Self
+	{
+		self.actions.push_back( Action::ReadError( kind ) );
+		self
+	}
+
+
+	/// The next write will fail with an error of `kind`.
+	//
+	pub fn write_error( mut self, kind: io::ErrorKind ) -> Self
+	{
+		self.actions.push_back( Action::WriteError( kind ) );
+		self
+	}
+
+
+	/// The clock used for [`MockStreamBuilder::wait`]. Defaults to a [`SystemClock`](crate::SystemClock).
+	/// On WASM there is no default, so you must set a clock if you use `wait`.
+	//
+	pub fn clock( mut self, clock: impl Clock + 'static ) -> Self
+	{
+		self.clock = Some( Arc::new( clock ) );
+		self
+	}
+
+
+	/// Create the mock stream.
+	///
+	/// # Panics
+	///
+	/// On WASM, when the script contains a wait and no clock was set.
+	//
+	pub fn build( self ) -> MockStream
+	{
+		let has_wait = self.actions.iter().any( |a| matches!( a, Action::Wait(_) ) );
+
+		let clock = match self.clock
+		{
+			Some(clock)      => Some( clock           ),
+			None if has_wait => Some( default_clock() ),
+			None             => None                   ,
+		};
+
+		let capacity = self.actions.iter().map( |a| match a
+		{
+			Action::Read(data) => data.len(),
+			_                  => 0         ,
+
+		}).max().unwrap_or( 0 ).max( 1 );
+
+		MockStream
+		{
+			actions    : self.actions                ,
+			incoming   : RingBuffer::new( capacity ) ,
+			clock                                    ,
+			deadline   : None                        ,
+			written    : 0                           ,
+			read_waker : None                        ,
+			write_waker: None                        ,
+			timer      : None                        ,
+		}
+	}
+}
+
+
+
+/// A mock connection that plays a script created with [`MockStreamBuilder`]. Use it to check that a codec
+/// or protocol implementation writes exactly what it should and handles what it reads correctly.
+///
+/// Implements `AsyncRead` and `AsyncWrite`.
+///
+/// # Panics
+///
+/// - When the code under test writes bytes that don't match the script, with a message that shows the offset
+///   and the expected and actual bytes.
+/// - When it writes when no more writes are expected.
+/// - When the mock stream is dropped before all scripted reads and writes have been done. This check is
+///   skipped if the thread is already panicking.
+//
+#[ derive( Debug ) ]
+//
+pub struct MockStream
+{
+	actions    : VecDeque<Action>         ,
+	incoming   : RingBuffer<u8>           ,
+	clock      : Option< Arc<dyn Clock> > ,
+	deadline   : Option<Duration>         ,
+	written    : usize                    ,
+	read_waker : Option<Waker>            ,
+	write_waker: Option<Waker>            ,
+
+	// The waker the clock will wake up at the deadline, so we don't ask again every time we are polled.
+	//
+	timer: Option<Waker>,
+}
+
+
+impl MockStream
+{
+	/// Whether all scripted reads and writes have been done.
+	//
+	pub fn is_done( &self ) -> bool
+	{
+		self.incoming.is_empty()
+		&& self.actions.iter().all( |a| matches!( a, Action::Wait(_) ) )
+	}
+
+
+	/// Check whether the wait at the front of the script has elapsed. If not, schedule a wake up.
+	//
+	fn poll_wait( &mut self, duration: Duration, cx: &mut Context<'_> ) -> Poll<()>
+	{
+		let clock    = self.clock.as_ref().expect( "MockStream with a wait always has a clock" );
+		let now      = clock.now();
+		let deadline = *self.deadline.get_or_insert( now + duration );
+
+		if now >= deadline
+		{
+			self.deadline = None;
+			self.timer    = None;
+			self.actions.pop_front();
+
+			// The other direction might be waiting on the same wait.
+			//
+			self.wake_reader();
+			self.wake_writer();
+
+			return Poll::Ready(());
+		}
+
+		if !matches!( &self.timer, Some(registered) if registered.will_wake( cx.waker() ) )
+		{
+			clock.wake_at( deadline, cx.waker().clone() );
+			self.timer = Some( cx.waker().clone() );
+		}
+
+		Poll::Pending
+	}
+
+
+	/// Compare what has been written with what the script expects. Returns how many bytes were accepted.
+	//
+	fn check_write( &mut self, index: usize, src: &[u8] ) -> usize
+	{
+		let expected = match &mut self.actions[ index ]
+		{
+			Action::Write(expected) => expected,
+			_                       => unreachable!(),
+		};
+
+		let n = src.len().min( expected.len() );
+
+		if let Some(pos) = ( 0..n ).find( |&i| src[i] != expected[i] )
+		{
+			panic!
+			(
+				"futures_ringbuf::MockStream: unexpected bytes written at offset {}.\nexpected: {}\n     got: {}\n",
+				self.written + pos,
+				show( &expected[ pos.. ] ),
+				show( &src     [ pos.. ] ),
+			);
+		}
+
+		expected.drain( ..n );
+
+		if expected.is_empty()
+		{
+			self.actions.remove( index );
+
+			// A read might be waiting for this write.
+			//
+			self.wake_reader();
+		}
+
+		self.written += n;
+
+		n
+	}
+
+
+	fn wake_reader( &mut self )
+	{
+		if let Some(waker) = self.read_waker.take() { waker.wake() }
+	}
+
+
+	fn wake_writer( &mut self )
+	{
+		if let Some(waker) = self.write_waker.take() { waker.wake() }
+	}
+}
+
+
+/// Show bytes like a byte string literal, escaping anything that isn't printable ascii.
+//
+pub(crate) fn show( bytes: &[u8] ) -> String
+{
+	let escaped: String = bytes.iter().flat_map( |b| std::ascii::escape_default( *b ) ).map( char::from ).collect();
+
+	format!( "b\"{}\"", escaped )
+}
+
+
+
+impl AsyncRead for MockStream
+{
+	/// Returns the scripted data. Pending while there are writes or a wait before the next read in the
+	/// script. Returns end of stream when the script has run out.
+	//
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, dst: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		loop
+		{
+			if !self.incoming.is_empty()
+			{
+				let read = ready!( Pin::new( &mut self.incoming ).poll_read( cx, dst ) );
+
+				// A write might be waiting for the reads before a wait.
+				//
+				if self.incoming.is_empty() { self.wake_writer() }
+
+				return read.into();
+			}
+
+			match self.actions.front().cloned()
+			{
+				None => return Ok(0).into(),
+
+				Some( Action::Read(data) ) =>
+				{
+					self.actions.pop_front();
+					self.incoming.producer.push_slice( &data );
+				}
+
+				Some( Action::ReadError(kind) ) =>
+				{
+					self.actions.pop_front();
+					self.wake_writer();
+					return Err( kind.into() ).into();
+				}
+
+				Some( Action::Wait(duration) ) => ready!( self.poll_wait( duration, cx ) ),
+
+				Some( Action::Write(_) | Action::WriteError(_) ) =>
+				{
+					self.read_waker = Some( cx.waker().clone() );
+					return Poll::Pending;
+				}
+			}
+		}
+	}
+}
+
+
+
+impl AsyncWrite for MockStream
+{
+	/// Checks `src` against the next write in the script, skipping over reads. Pending while there is a wait
+	/// before the next write.
+	//
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, src: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		if src.is_empty()
+		{
+			return Ok(0).into();
+		}
+
+		loop
+		{
+			let next = self.actions.iter().position( |a| a.is_write() || matches!( a, Action::Wait(_) ) );
+
+			match next.map( |i| (i, self.actions[i].clone()) )
+			{
+				None => panic!
+				(
+					"futures_ringbuf::MockStream: unexpected write at offset {}, the script has no more writes.\n     got: {}\n",
+					self.written,
+					show( src ),
+				),
+
+				Some(( i, Action::WriteError(kind) )) =>
+				{
+					self.actions.remove( i );
+					return Err( kind.into() ).into();
+				}
+
+				// Waits are only started once all reads before them are done.
+				//
+				Some(( 0, Action::Wait(duration) )) if self.incoming.is_empty() => ready!( self.poll_wait( duration, cx ) ),
+
+				Some(( _, Action::Wait(_) )) =>
+				{
+					self.write_waker = Some( cx.waker().clone() );
+					return Poll::Pending;
+				}
+
+				Some(( i, _ )) => return Ok( self.check_write( i, src ) ).into(),
+			}
+		}
+	}
+
+
+	/// This is a noop.
+	//
+	fn poll_flush( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		Ok(()).into()
+	}
+
+
+	/// This is a noop. Use [`MockStream::is_done`] to check that the script has been played.
+	//
+	fn poll_close( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		Ok(()).into()
+	}
+}
+
+
+
+impl Drop for MockStream
+{
+	fn drop( &mut self )
+	{
+		if !std::thread::panicking() && !self.is_done()
+		{
+			let remaining: Vec<_> = self.actions.iter().filter( |a| !matches!( a, Action::Wait(_) ) ).collect();
+
+			panic!
+			(
+				"futures_ringbuf::MockStream: dropped before the script was done, {} bytes left unread, remaining: {:?}",
+				self.incoming.len(),
+				remaining,
+			);
+		}
+	}
+}
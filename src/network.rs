@@ -0,0 +1,244 @@
+use crate::{ import::*, Endpoint, EndpointBuilder };
+use futures::{ StreamExt, channel::mpsc::{ unbounded, UnboundedSender, UnboundedReceiver } };
+use std::collections::HashMap;
+
+
+/// Client ports are counted from here.
+//
+const EPHEMERAL_PORT: u16 = 49152;
+
+
+/// An in-memory network to test code that accepts connections in a loop. Servers [`bind`](MockNetwork::bind)
+/// to an address, which can be any string, and clients [`connect`](MockNetwork::connect) to it. Every
+/// connection is a pair of [`Endpoint`]s.
+///
+/// Clones refer to the same network.
+///
+/// ```
+/// use futures_ringbuf::MockNetwork;
+/// use futures::{ AsyncReadExt, AsyncWriteExt, executor::block_on };
+///
+/// let network      = MockNetwork::new();
+/// let mut listener = network.bind( "server:1234" ).expect( "bind" );
+///
+/// block_on( async
+/// {
+///    let mut client = network.connect( "server:1234" ).expect( "connect" );
+///    let mut server = listener.accept().await.expect( "accept" );
+///
+///    client.write_all( b"hello" ).await.expect( "write" );
+///
+///    let mut buf = [0u8; 5];
+///    server.read_exact( &mut buf ).await.expect( "read" );
+/// });
+///
+/// assert!( network.connect( "elsewhere:1234" ).is_err() );
+/// ```
+//
+#[ derive( Debug, Clone, Default ) ]
+//
+pub struct MockNetwork
+{
+	inner: Arc< Mutex<NetworkInner> >,
+}
+
+
+#[ derive( Debug, Default ) ]
+//
+struct NetworkInner
+{
+	listeners  : HashMap< String, UnboundedSender<Endpoint> > ,
+	builder    : EndpointBuilder                              ,
+	connections: usize                                        ,
+}
+
+
+impl MockNetwork
+{
+	/// Create an empty network. Connections use the default configuration of [`EndpointBuilder`].
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// Create an empty network where connections are created by `builder`, so you can give them
+	/// latency, limited bandwidth or record them. The names set on the builder are replaced: the
+	/// server side is named after the address and the client side `client-N`, where _N_ counts the
+	/// connections on this network, starting from 1.
+	///
+	/// The addresses set on the builder are replaced too. The client side gets `127.0.0.1` with a port
+	/// counting up from 49152. The server side gets the address passed to `connect` if it's a valid
+	/// socket address, otherwise the address set on the builder.
+	///
+	/// If the builder has latency or bandwidth but no clock, all connections share one default clock.
+	///
+	/// # Panics
+	///
+	/// On WASM, when time based behavior is configured without setting a clock.
+	//
+	pub fn with_builder( mut builder: EndpointBuilder ) -> Self
+	{
+		builder.share_clock();
+
+		let inner = NetworkInner { builder, ..Default::default() };
+
+		Self { inner: Arc::new( Mutex::new( inner ) ) }
+	}
+
+
+	/// Listen for connections on `addr`.
+	///
+	/// # Errors
+	///
+	/// `io::ErrorKind::AddrInUse` when there is already a listener on `addr`.
+	//
+	pub fn bind( &self, addr: impl Into<String> ) -> io::Result<MockListener>
+	{
+		let addr      = addr.into();
+		let mut inner = self.lock();
+
+		if inner.listeners.contains_key( &addr )
+		{
+			return Err( io::Error::new( io::ErrorKind::AddrInUse, format!( "futures_ringbuf::MockNetwork: {} is already bound", addr ) ) );
+		}
+
+		let (tx, rx) = unbounded();
+
+		inner.listeners.insert( addr.clone(), tx );
+
+		Ok( MockListener { addr, incoming: rx, network: self.clone() } )
+	}
+
+
+	/// Connect to the listener on `addr`. The connection is established right away, the server side
+	/// waits in the queue of the listener until it is accepted.
+	///
+	/// # Errors
+	///
+	/// `io::ErrorKind::ConnectionRefused` when nothing is listening on `addr`.
+	//
+	pub fn connect( &self, addr: &str ) -> io::Result<Endpoint>
+	{
+		let mut inner = self.lock();
+
+		let refused = || io::Error::new( io::ErrorKind::ConnectionRefused, format!( "futures_ringbuf::MockNetwork: nothing listens on {}", addr ) );
+
+		if !inner.listeners.contains_key( addr )
+		{
+			return Err( refused() );
+		}
+
+		inner.connections += 1;
+
+		let client_addr = SocketAddr::new( Ipv4Addr::LOCALHOST.into(), EPHEMERAL_PORT + ( ( inner.connections - 1 ) % 16384 ) as u16 );
+
+		let mut builder = inner.builder.clone()
+
+			.a_name( format!( "client-{}", inner.connections ) )
+			.b_name( addr )
+			.a_addr( client_addr )
+		;
+
+		if let Ok(server_addr) = addr.parse()
+		{
+			builder = builder.b_addr( server_addr );
+		}
+
+		let (client, server) = builder.build();
+
+		log::trace!( "MockNetwork: {} connects to {}", client.name(), addr );
+
+		inner.listeners[ addr ].unbounded_send( server ).map_err( |_| refused() )?;
+
+		Ok( client )
+	}
+
+
+	fn lock( &self ) -> std::sync::MutexGuard<'_, NetworkInner>
+	{
+		self.inner.lock().unwrap_or_else( PoisonError::into_inner )
+	}
+}
+
+
+
+/// Accepts connections on an address of a [`MockNetwork`]. Implements `Stream<Item = io::Result<Endpoint>>`,
+/// like the incoming connections of a TCP listener.
+///
+/// Dropping the listener frees the address. Connections that haven't been accepted yet are dropped, so the
+/// client will see the end of the stream.
+//
+#[ derive( Debug ) ]
+//
+pub struct MockListener
+{
+	addr    : String                      ,
+	incoming: UnboundedReceiver<Endpoint> ,
+	network : MockNetwork                 ,
+}
+
+
+impl MockListener
+{
+	/// The address this listener is bound to, as passed to [`MockNetwork::bind`].
+	//
+	pub fn addr( &self ) -> &str
+	{
+		&self.addr
+	}
+
+
+	/// The local address of the connections accepted by this listener, like `TcpListener::local_addr`.
+	/// It's the address this listener is bound to if that's a valid socket address, otherwise the address
+	/// set with `EndpointBuilder::b_addr` on the builder of the network.
+	///
+	/// This never fails, it returns a `Result` to match [`Endpoint::local_addr`].
+	//
+	pub fn local_addr( &self ) -> io::Result<SocketAddr>
+	{
+		Ok( self.addr.parse().unwrap_or_else( |_| self.network.lock().builder.server_addr() ) )
+	}
+
+
+	/// Wait for the next connection.
+	//
+	pub async fn accept( &mut self ) -> io::Result<Endpoint>
+	{
+		// The network keeps a sender as long as we are bound, so the stream doesn't end.
+		//
+		self.next().await.unwrap_or_else( || Err( io::ErrorKind::NotConnected.into() ) )
+	}
+}
+
+
+impl Stream for MockListener
+{
+	type Item = io::Result<Endpoint>;
+
+	/// Never returns `None`, the stream of connections only ends when the listener is dropped.
+	//
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		self.incoming.poll_next_unpin( cx ).map( |endpoint| endpoint.map( Ok ) )
+	}
+}
+
+
+impl Drop for MockListener
+{
+	fn drop( &mut self )
+	{
+		self.incoming.close();
+
+		let mut inner = self.network.lock();
+
+		// The address might have been bound again by someone else.
+		//
+		if matches!( inner.listeners.get( &self.addr ), Some(tx) if tx.is_closed() )
+		{
+			inner.listeners.remove( &self.addr );
+		}
+	}
+}
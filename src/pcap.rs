@@ -0,0 +1,254 @@
+//! Export a [`Transcript`] in the pcap format, so it can be opened in Wireshark. TCP/IP headers are
+//! synthesized: every endpoint gets a fake IPv4 address and every pair of endpoints a TCP connection,
+//! starting with a three way handshake and with correct sequence and acknowledgement numbers.
+//
+use crate::{ import::*, Transcript };
+use std::collections::HashMap;
+
+
+// Nanosecond resolution pcap.
+//
+const MAGIC   : u32 = 0xa1b2_3c4d;
+const LINKTYPE: u32 = 101; // LINKTYPE_RAW, packets start with the IP header.
+const SNAPLEN : u32 = 65535;
+
+const IP_HEADER : usize = 20;
+const TCP_HEADER: usize = 20;
+const MAX_DATA  : usize = 65535 - IP_HEADER - TCP_HEADER;
+
+const SYN: u8 = 0x02;
+const PSH: u8 = 0x08;
+const ACK: u8 = 0x10;
+
+/// The ports of endpoints that aren't the server start from here.
+//
+const EPHEMERAL_PORT: u16 = 49152;
+
+/// How many ephemeral ports there are, after that they are reused.
+//
+const EPHEMERAL_PORTS: usize = 16384;
+
+
+#[ derive( Debug, Clone, Copy ) ]
+//
+struct Host
+{
+	ip  : [u8; 4] ,
+	port: u16     ,
+}
+
+
+/// The next sequence number for both sides of a connection. The one who wrote first opened the connection.
+//
+#[ derive( Debug ) ]
+//
+struct Connection<'a>
+{
+	opener: &'a str  ,
+	seq   : [u32; 2] ,
+}
+
+
+impl Connection<'_>
+{
+	fn side( &self, name: &str ) -> usize
+	{
+		if name == self.opener { 0 } else { 1 }
+	}
+}
+
+
+impl Transcript
+{
+	/// Write the transcript to `out` in the pcap format. The endpoint called `server` gets `port`, so
+	/// Wireshark can recognize the protocol, the others get an ephemeral port. Endpoints get the addresses
+	/// 10.0.0.1, 10.0.0.2, ... in the order they first appear in the transcript.
+	///
+	/// With more than 16384 endpoints, ephemeral ports are reused, and with more than 65535 endpoints,
+	/// addresses are.
+	///
+	/// Records without timestamp are put one microsecond apart.
+	//
+	pub fn write_pcap( &self, mut out: impl io::Write, server: &str, port: u16 ) -> io::Result<()>
+	{
+		out.write_all( &MAGIC   .to_le_bytes() )?;
+		out.write_all( &2u16    .to_le_bytes() )?; // version major
+		out.write_all( &4u16    .to_le_bytes() )?; // version minor
+		out.write_all( &0i32    .to_le_bytes() )?; // timezone
+		out.write_all( &0u32    .to_le_bytes() )?; // timestamp accuracy
+		out.write_all( &SNAPLEN .to_le_bytes() )?;
+		out.write_all( &LINKTYPE.to_le_bytes() )?;
+
+		let mut hosts       = HashMap::<&str, Host>::new();
+		let mut connections = HashMap::<(&str, &str), Connection<'_>>::new();
+
+		for record in self.records()
+		{
+			for name in [ record.from(), record.to() ]
+			{
+				let n    = hosts.len() % 65535 + 1;
+				let port = if name == server { port } else { EPHEMERAL_PORT + ( ( n - 1 ) % EPHEMERAL_PORTS ) as u16 };
+
+				hosts.entry( name ).or_insert( Host
+				{
+					ip  : [ 10, 0, ( n >> 8 ) as u8, n as u8 ] ,
+					port                                       ,
+				});
+			}
+
+			let time = record.timestamp().unwrap_or_else( || Duration::from_micros( record.seq() as u64 ) );
+			let from = hosts[ record.from() ];
+			let to   = hosts[ record.to()   ];
+			let key  = ( record.from().min( record.to() ), record.from().max( record.to() ) );
+
+			let conn = match connections.get_mut( &key )
+			{
+				Some(conn) => conn,
+
+				None =>
+				{
+					packet( &mut out, time, from, to, 0, 0, SYN      , &[] )?;
+					packet( &mut out, time, to, from, 0, 1, SYN | ACK, &[] )?;
+					packet( &mut out, time, from, to, 1, 1, ACK      , &[] )?;
+
+					connections.entry( key ).or_insert( Connection { opener: record.from(), seq: [ 1, 1 ] } )
+				}
+			};
+
+			let ours   = conn.side( record.from() );
+			let theirs = 1 - ours;
+
+			for chunk in record.data().chunks( MAX_DATA )
+			{
+				packet( &mut out, time, from, to, conn.seq[ ours ], conn.seq[ theirs ], PSH | ACK, chunk )?;
+
+				conn.seq[ ours ] = conn.seq[ ours ].wrapping_add( chunk.len() as u32 );
+			}
+		}
+
+		Ok(())
+	}
+
+
+	/// Save the transcript as a pcap file. See [`Transcript::write_pcap`].
+	//
+	pub fn save_pcap( &self, path: impl AsRef<std::path::Path>, server: &str, port: u16 ) -> io::Result<()>
+	{
+		let mut out = io::BufWriter::new( std::fs::File::create( path )? );
+
+		self.write_pcap( &mut out, server, port )?;
+
+		io::Write::flush( &mut out )
+	}
+}
+
+
+/// Write one pcap record containing an IPv4 packet with a TCP segment.
+//
+#[ allow( clippy::too_many_arguments ) ]
+//
+fn packet
+(
+	out  : &mut impl io::Write ,
+	time : Duration            ,
+	from : Host                ,
+	to   : Host                ,
+	seq  : u32                 ,
+	ack  : u32                 ,
+	flags: u8                  ,
+	data : &[u8]               ,
+)
+	-> io::Result<()>
+{
+	let total = IP_HEADER + TCP_HEADER + data.len();
+
+	let mut ip = Vec::with_capacity( total );
+
+	ip.extend_from_slice( &[ 0x45, 0 ]                     ); // version 4, header length 5 words, dscp
+	ip.extend_from_slice( &( total as u16 ).to_be_bytes()  );
+	ip.extend_from_slice( &[ 0, 0, 0x40, 0 ]               ); // identification, don't fragment
+	ip.extend_from_slice( &[ 64, 6, 0, 0 ]                 ); // ttl, protocol tcp, checksum
+	ip.extend_from_slice( &from.ip                         );
+	ip.extend_from_slice( &to.ip                           );
+
+	let ip_checksum = checksum( &ip, 0 );
+	ip[ 10..12 ].copy_from_slice( &ip_checksum.to_be_bytes() );
+
+	let tcp_start = ip.len();
+
+	ip.extend_from_slice( &from.port.to_be_bytes() );
+	ip.extend_from_slice( &to  .port.to_be_bytes() );
+	ip.extend_from_slice( &seq      .to_be_bytes() );
+	ip.extend_from_slice( &ack      .to_be_bytes() );
+	ip.extend_from_slice( &[ 5 << 4, flags ]       ); // header length 5 words, flags
+	ip.extend_from_slice( &u16::MAX .to_be_bytes() ); // window
+	ip.extend_from_slice( &[ 0, 0, 0, 0 ]          ); // checksum, urgent pointer
+	ip.extend_from_slice( data                     );
+
+	// The tcp checksum includes a pseudo header with the addresses, the protocol and the tcp length.
+	//
+	let mut pseudo = Vec::with_capacity( 12 );
+
+	pseudo.extend_from_slice( &from.ip );
+	pseudo.extend_from_slice( &to  .ip );
+	pseudo.extend_from_slice( &[ 0, 6 ] );
+	pseudo.extend_from_slice( &( ( total - IP_HEADER ) as u16 ).to_be_bytes() );
+
+	let tcp_checksum = checksum( &ip[ tcp_start.. ], sum( &pseudo ) );
+	ip[ tcp_start + 16..tcp_start + 18 ].copy_from_slice( &tcp_checksum.to_be_bytes() );
+
+	out.write_all( &( time.as_secs() as u32 ).to_le_bytes() )?;
+	out.write_all( &time.subsec_nanos()       .to_le_bytes() )?;
+	out.write_all( &( total as u32 )          .to_le_bytes() )?;
+	out.write_all( &( total as u32 )          .to_le_bytes() )?;
+	out.write_all( &ip )
+}
+
+
+/// The one's complement sum of 16 bit words used by IP and TCP, not yet folded.
+//
+fn sum( data: &[u8] ) -> u32
+{
+	data.chunks( 2 ).map( |w| u32::from( w[0] ) << 8 | u32::from( *w.get( 1 ).unwrap_or( &0 ) ) ).sum()
+}
+
+
+/// The internet checksum of `data`, starting from `initial`.
+//
+fn checksum( data: &[u8], initial: u32 ) -> u16
+{
+	let mut sum = initial + sum( data );
+
+	while sum > 0xffff
+	{
+		sum = ( sum & 0xffff ) + ( sum >> 16 );
+	}
+
+	!( sum as u16 )
+}
+
+
+
+#[ cfg(test) ]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ checksum of a known ip header
+	//
+	use crate::import::assert_eq;
+	use super::*;
+
+
+	#[test]
+	//
+	fn ip_checksum()
+	{
+		// Example from wikipedia, with the checksum zeroed.
+		//
+		let header = [ 0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 0xc0, 0xa8, 0x00, 0x01, 0xc0, 0xa8, 0x00, 0xc7 ];
+
+		assert_eq!( checksum( &header, 0 ), 0xb861 );
+	}
+}
@@ -0,0 +1,462 @@
+use crate::{ import::*, Clock };
+
+
+/// Records the data written on endpoints, so you can see exactly what went over the wire and in which
+/// order when a protocol test fails. Attach it to a pair of endpoints with [`EndpointBuilder::recorder`](crate::EndpointBuilder::recorder).
+///
+/// Every successful write is recorded as a [`Record`]. Clones share the same records, so keep a clone to
+/// look at the [`Transcript`] after the test. The same recorder can be attached to several pairs of endpoints.
+///
+/// ```
+/// use futures_ringbuf::{ EndpointBuilder, Recorder };
+/// use futures::{ AsyncWriteExt, executor::block_on };
+///
+/// let recorder = Recorder::new();
+///
+/// let (mut server, _client) = EndpointBuilder::new()
+///
+///    .a_name  ( "server"         )
+///    .b_name  ( "client"         )
+///    .recorder( recorder.clone() )
+///    .build()
+/// ;
+///
+/// block_on( server.write_all( b"hello" ) ).expect( "write" );
+///
+/// let transcript = recorder.transcript();
+///
+/// assert_eq!( transcript.records()[0].from(), "server" );
+/// assert_eq!( transcript.records()[0].data(), b"hello" );
+///
+/// println!( "{}", transcript );
+/// ```
+//
+#[ derive( Debug, Clone, Default ) ]
+//
+pub struct Recorder
+{
+	records: Arc< Mutex< Vec<Record> > >,
+}
+
+
+impl Recorder
+{
+	/// Create an empty recorder.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// A copy of everything recorded so far.
+	//
+	pub fn transcript( &self ) -> Transcript
+	{
+		Transcript { records: self.lock().clone() }
+	}
+
+
+	/// Forget everything recorded so far. Sequence numbers start again from zero.
+	//
+	pub fn clear( &self )
+	{
+		self.lock().clear();
+	}
+
+
+	fn lock( &self ) -> std::sync::MutexGuard<'_, Vec<Record>>
+	{
+		self.records.lock().unwrap_or_else( PoisonError::into_inner )
+	}
+}
+
+
+
+/// One chunk of data accepted by a successful write on an endpoint.
+//
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+//
+pub struct Record
+{
+	seq      : usize            ,
+	from     : String           ,
+	to       : String           ,
+	timestamp: Option<Duration> ,
+	data     : Vec<u8>          ,
+}
+
+
+impl Record
+{
+	/// The position of this record in the transcript, counting from zero.
+	//
+	pub fn seq( &self ) -> usize
+	{
+		self.seq
+	}
+
+
+	/// The name of the endpoint that wrote the data.
+	//
+	pub fn from( &self ) -> &str
+	{
+		&self.from
+	}
+
+
+	/// The name of the endpoint the data was written to.
+	//
+	pub fn to( &self ) -> &str
+	{
+		&self.to
+	}
+
+
+	/// The time of the write according to the clock of the endpoints, if one was set with
+	/// [`EndpointBuilder::clock`](crate::EndpointBuilder::clock) or is used for latency or bandwidth.
+	//
+	pub fn timestamp( &self ) -> Option<Duration>
+	{
+		self.timestamp
+	}
+
+
+	/// The bytes that were written.
+	//
+	pub fn data( &self ) -> &[u8]
+	{
+		&self.data
+	}
+}
+
+
+impl fmt::Display for Record
+{
+	/// A header line followed by a hex and ascii dump of the data.
+	//
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "#{} {} -> {}", self.seq, self.from, self.to )?;
+
+		if let Some(timestamp) = self.timestamp
+		{
+			write!( f, " @ {:?}", timestamp )?;
+		}
+
+		writeln!( f, ", {} bytes", self.data.len() )?;
+
+		for (i, line) in self.data.chunks( 16 ).enumerate()
+		{
+			write!( f, "  {:08x} ", i * 16 )?;
+
+			for j in 0..16
+			{
+				if j == 8 { write!( f, " " )? }
+
+				match line.get( j )
+				{
+					Some(b) => write!( f, " {:02x}", b )?,
+					None    => write!( f, "   "       )?,
+				}
+			}
+
+			let ascii: String = line.iter().map( |&b|
+
+				if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }
+
+			).collect();
+
+			writeln!( f, "  |{}|", ascii )?;
+		}
+
+		Ok(())
+	}
+}
+
+
+
+/// Everything a [`Recorder`] has captured, in the order it was written. `Display` shows a hex dump
+/// of all records.
+//
+#[ derive( Debug, Clone, Default, PartialEq, Eq ) ]
+//
+pub struct Transcript
+{
+	records: Vec<Record>,
+}
+
+
+impl Transcript
+{
+	/// All records, in the order they were written.
+	//
+	pub fn records( &self ) -> &[Record]
+	{
+		&self.records
+	}
+
+
+	/// The records of the data written by the endpoint called `from`.
+	//
+	pub fn written_by<'a>( &'a self, from: &'a str ) -> impl Iterator<Item = &'a Record> + 'a
+	{
+		self.records.iter().filter( move |r| r.from == from )
+	}
+
+
+	/// All data written by the endpoint called `from`, concatenated.
+	//
+	pub fn bytes_written_by( &self, from: &str ) -> Vec<u8>
+	{
+		self.written_by( from ).flat_map( |r| r.data.iter().copied() ).collect()
+	}
+}
+
+
+impl fmt::Display for Transcript
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		for record in &self.records
+		{
+			write!( f, "{}", record )?;
+		}
+
+		Ok(())
+	}
+}
+
+
+impl Transcript
+{
+	/// Save the transcript to a file, in the same format as `Display`. It can be read back with
+	/// [`Transcript::load`], so a recorded session can be turned into a regression test.
+	//
+	pub fn save( &self, path: impl AsRef<std::path::Path> ) -> io::Result<()>
+	{
+		std::fs::write( path, self.to_string() )
+	}
+
+
+	/// Load a transcript saved with [`Transcript::save`].
+	///
+	/// # Errors
+	///
+	/// Errors from reading the file and `io::ErrorKind::InvalidData` if it can't be parsed.
+	//
+	pub fn load( path: impl AsRef<std::path::Path> ) -> io::Result<Self>
+	{
+		std::fs::read_to_string( path )?.parse()
+	}
+}
+
+
+impl std::str::FromStr for Transcript
+{
+	type Err = io::Error;
+
+	/// Parse the output of `Display`. Empty lines and indentation are ignored.
+	//
+	fn from_str( s: &str ) -> io::Result<Self>
+	{
+		let mut records = Vec::<Record>::new();
+
+		for (nr, line) in s.lines().enumerate()
+		{
+			let invalid = |what: &str| io::Error::new
+			(
+				io::ErrorKind::InvalidData,
+				format!( "futures_ringbuf::Transcript: {} on line {}: {:?}", what, nr + 1, line ),
+			);
+
+			if line.trim().is_empty() { continue }
+
+			if let Some(header) = line.trim_start().strip_prefix( '#' )
+			{
+				records.push( parse_header( header ).ok_or_else( || invalid( "invalid header" ) )? );
+				continue;
+			}
+
+			let record = records.last_mut().ok_or_else( || invalid( "data before the first header" ) )?;
+
+			// The hex part is between the offset and the ascii column.
+			//
+			let hex = line.split( '|' ).next().unwrap_or_default();
+			let mut words = hex.split_whitespace();
+
+			words.next().ok_or_else( || invalid( "missing offset" ) )?;
+
+			for word in words
+			{
+				record.data.push( u8::from_str_radix( word, 16 ).map_err( |_| invalid( "invalid byte" ) )? );
+			}
+		}
+
+		Ok( Self { records } )
+	}
+}
+
+
+/// Parse `0 a -> b @ 5ms, 3 bytes`, the length is only informative.
+//
+fn parse_header( header: &str ) -> Option<Record>
+{
+	let (header, _len)  = header.rsplit_once( ", " )?;
+	let (seq, header)   = header.split_once( ' ' )?;
+	let (from, header)  = header.split_once( " -> " )?;
+
+	let (to, timestamp) = match header.split_once( " @ " )
+	{
+		Some((to, ts)) => (to, Some( parse_duration( ts )? )),
+		None           => (header, None),
+	};
+
+	Some( Record
+	{
+		seq      : seq.parse().ok()? ,
+		from     : from.to_string()  ,
+		to       : to  .to_string()  ,
+		data     : Vec::new()        ,
+		timestamp                    ,
+	})
+}
+
+
+/// Parse the `Debug` output of a `Duration`, like `1.5ms`.
+//
+fn parse_duration( s: &str ) -> Option<Duration>
+{
+	let split         = s.find( |c: char| !c.is_ascii_digit() && c != '.' )?;
+	let (value, unit) = s.split_at( split );
+
+	let unit: u128 = match unit
+	{
+		"ns" => 1             ,
+		"µs" => 1_000         ,
+		"ms" => 1_000_000     ,
+		"s"  => 1_000_000_000 ,
+		_    => return None   ,
+	};
+
+	let (int, frac) = value.split_once( '.' ).unwrap_or(( value, "" ));
+
+	let mut nanos = int.parse::<u128>().ok()? * unit;
+	let mut scale = unit;
+
+	for digit in frac.chars()
+	{
+		scale /= 10;
+		nanos += digit.to_digit( 10 )? as u128 * scale;
+	}
+
+	Some( Duration::new( ( nanos / 1_000_000_000 ) as u64, ( nanos % 1_000_000_000 ) as u32 ) )
+}
+
+
+
+/// Records the writes of one direction of a pair of endpoints.
+//
+#[ derive( Debug ) ]
+//
+pub(crate) struct Tap
+{
+	recorder: Recorder                 ,
+	from    : String                   ,
+	to      : String                   ,
+	clock   : Option< Arc<dyn Clock> > ,
+}
+
+
+impl Tap
+{
+	pub(crate) fn new( recorder: Recorder, from: &str, to: &str, clock: Option< Arc<dyn Clock> > ) -> Self
+	{
+		Self { recorder, from: from.to_string(), to: to.to_string(), clock }
+	}
+
+
+	pub(crate) fn record( &self, data: &[u8] )
+	{
+		let timestamp   = self.clock.as_ref().map( |c| c.now() );
+		let mut records = self.recorder.lock();
+
+		let record = Record
+		{
+			seq : records.len()      ,
+			from: self.from.clone()  ,
+			to  : self.to  .clone()  ,
+			data: data.to_vec()      ,
+			timestamp                ,
+		};
+
+		records.push( record );
+	}
+}
+
+
+
+#[ cfg(test) ]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ hex dump of a record
+	// ✔ parsing the hex dump back into a transcript
+	// ✔ parsing durations
+	//
+	use crate::import::assert_eq;
+	use super::*;
+
+
+	#[test]
+	//
+	fn display()
+	{
+		let recorder = Recorder::new();
+
+		Tap::new( recorder.clone(), "a", "b", None ).record( b"hello world, this is\0a test" );
+
+		let expected = "\
+			#0 a -> b, 27 bytes\n\
+			\x20 00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 2c 20 74 68 69  |hello world, thi|\n\
+			\x20 00000010  73 20 69 73 00 61 20 74  65 73 74                 |s is.a test|\n\
+		";
+
+		assert_eq!( recorder.transcript().to_string(), expected );
+	}
+
+
+	#[test]
+	//
+	fn parse()
+	{
+		let recorder = Recorder::new();
+		let clock    = Arc::new( crate::VirtualClock::new() );
+
+		Tap::new( recorder.clone(), "a"  , "b c", None                  ).record( &( 0..=255 ).collect::<Vec<u8>>() );
+		Tap::new( recorder.clone(), "b c", "a"  , Some( clock.clone() ) ).record( b"|pipes| and spaces "          );
+
+		clock.advance( Duration::from_micros( 1500 ) );
+
+		Tap::new( recorder.clone(), "b c", "a", Some( clock ) ).record( b"x" );
+
+		let transcript = recorder.transcript();
+
+		assert_eq!( transcript.to_string().parse::<Transcript>().expect( "parse" ), transcript );
+
+		assert_eq!( "nonsense".parse::<Transcript>().expect_err( "parse" ).kind(), io::ErrorKind::InvalidData );
+	}
+
+
+	#[test]
+	//
+	fn durations()
+	{
+		for d in [ Duration::ZERO, Duration::from_nanos( 7 ), Duration::from_nanos( 1_000_001 ), Duration::from_secs( 3 ), Duration::new( 1, 5 ) ]
+		{
+			assert_eq!( parse_duration( &format!( "{:?}", d ) ), Some( d ) );
+		}
+	}
+}
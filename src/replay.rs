@@ -0,0 +1,154 @@
+use crate::{ import::*, Endpoint, EndpointBuilder, Transcript, Record, mock::show };
+use futures::{ AsyncReadExt, AsyncWriteExt, future::BoxFuture };
+
+
+/// Drives the peer of an endpoint created with [`Transcript::replay`]. It must be polled for the
+/// peer to respond, so spawn it or join it with the code under test.
+///
+/// Resolves to an error with `io::ErrorKind::InvalidData` when the code under test writes something
+/// else than what was recorded, or with the error of the connection if it fails. When the whole
+/// transcript has been played, the peer closes the connection.
+//
+#[ must_use = "futures do nothing unless polled" ]
+//
+pub struct Replay
+{
+	future: BoxFuture< 'static, io::Result<()> >,
+}
+
+
+impl Future for Replay
+{
+	type Output = io::Result<()>;
+
+	fn poll( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Self::Output>
+	{
+		self.future.as_mut().poll( cx )
+	}
+}
+
+
+impl fmt::Debug for Replay
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "Replay" ).finish_non_exhaustive()
+	}
+}
+
+
+
+impl Transcript
+{
+	/// Replay one side of a recorded conversation. Returns an endpoint for the code under test and a
+	/// [`Replay`] that plays the endpoint called `peer` in the transcript: it sends what `peer` wrote
+	/// once the code under test has written everything that was recorded before it.
+	///
+	/// ```
+	/// use futures_ringbuf::{ Transcript };
+	/// use futures::{ AsyncReadExt, AsyncWriteExt, executor::block_on, future::join };
+	///
+	/// // Normally you would use Transcript::load on a file saved after a recorded session.
+	/// //
+	/// let transcript: Transcript = "
+	///    #0 client -> server, 4 bytes
+	///      00000000  70 69 6e 67                                       |ping|
+	///    #1 server -> client, 4 bytes
+	///      00000000  70 6f 6e 67                                       |pong|
+	/// ".parse().expect( "parse" );
+	///
+	/// let (mut client, server) = transcript.replay( "server" );
+	///
+	/// let test = async
+	/// {
+	///    let mut pong = Vec::new();
+	///
+	///    client.write_all( b"ping" ).await.expect( "write" );
+	///    client.read_to_end( &mut pong ).await.expect( "read" );
+	///
+	///    assert_eq!( pong, b"pong" );
+	/// };
+	///
+	/// let (_, replay) = block_on( join( test, server ) );
+	///
+	/// replay.expect( "the client behaved as recorded" );
+	/// ```
+	//
+	pub fn replay( &self, peer: &str ) -> (Endpoint, Replay)
+	{
+		self.replay_with( EndpointBuilder::new(), peer )
+	}
+
+
+	/// Like [`Transcript::replay`], but the endpoints are created by `builder`, so you can combine
+	/// the replay with latency, bandwidth limits or recording. The names set on the builder are
+	/// replaced by the names in the transcript.
+	//
+	pub fn replay_with( &self, builder: EndpointBuilder, peer: &str ) -> (Endpoint, Replay)
+	{
+		let local = self.records().iter()
+
+			.find( |r| r.from() != peer )
+			.map( |r| r.from().to_string() )
+			.unwrap_or_else( || "local".to_string() )
+		;
+
+		let (local, remote) = builder.a_name( local ).b_name( peer ).build();
+
+		let records = self.records().to_vec();
+		let future  = Box::pin( play( records, remote ) );
+
+		(local, Replay{ future })
+	}
+}
+
+
+async fn play( records: Vec<Record>, mut remote: Endpoint ) -> io::Result<()>
+{
+	let mut offset = 0;
+	let mut i      = 0;
+
+	while i < records.len()
+	{
+		if records[i].from() == remote.name()
+		{
+			remote.write_all( records[i].data() ).await?;
+			i += 1;
+			continue;
+		}
+
+		// Read everything the code under test wrote before the next response at once, it doesn't
+		// have to write in the same chunks as recorded.
+		//
+		let mut expected = Vec::new();
+
+		while i < records.len() && records[i].from() != remote.name()
+		{
+			expected.extend_from_slice( records[i].data() );
+			i += 1;
+		}
+
+		let mut got = vec![ 0u8; expected.len() ];
+
+		remote.read_exact( &mut got ).await?;
+
+		if let Some(pos) = ( 0..got.len() ).find( |&p| got[p] != expected[p] )
+		{
+			let msg = format!
+			(
+				"futures_ringbuf::Replay: unexpected bytes written at offset {}.\nexpected: {}\n     got: {}\n",
+				offset + pos,
+				show( &expected[ pos.. ] ),
+				show( &got     [ pos.. ] ),
+			);
+
+			log::error!( "{}", msg );
+
+			return Err( io::Error::new( io::ErrorKind::InvalidData, msg ) );
+		}
+
+		offset += got.len();
+	}
+
+	remote.close().await
+}
@@ -1,15 +1,17 @@
 use crate::import::*;
 
-type Producer<T> = ringbuf::HeapProducer<T>;
-type Consumer<T> = ringbuf::HeapConsumer<T>;
+pub(crate) type Producer<T> = ringbuf::HeapProducer<T>;
+pub(crate) type Consumer<T> = ringbuf::HeapConsumer<T>;
 
-/// A RingBuffer that implements `AsyncRead` and `AsyncWrite` from the futures library.
+/// A RingBuffer that implements `AsyncRead` and `AsyncWrite` from the futures library when `T` is `u8`,
+/// and `Stream`/`Sink` for any `T`.
 ///
 /// This object is rather special in that it's read and writes are connected to a single
 /// ringbuffer. It's good for low level unit tests for (eg. framing a connection with a
 /// codec) and verifying that a codec writes the correct data, but it does not mock a full
-/// network connection. Subtle things can go wrong, like when using `AsyncRead::split` and
+/// network connection. Subtle things can go wrong, like when using `AsyncReadExt::split` and
 /// dropping the `WriteHalf`, the `ReadHalf` cannot detect that and the task won't be woken up.
+/// Use [`RingBuffer::split`] instead, which doesn't have that problem.
 ///
 /// If you want to mock a network connection, use [Endpoint](crate::Endpoint).
 //
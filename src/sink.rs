@@ -0,0 +1,167 @@
+use crate::{ import::*, RingBuffer };
+
+
+
+impl<T: Sized + Copy + Unpin> Sink<T> for RingBuffer<T>
+{
+	type Error = io::Error;
+
+	/// Will return Poll::Pending when the buffer is full. The Stream impl will wake up this task
+	/// when new place is made.
+	/// This method returns a `io::ErrorKind::NotConnected` error if called after `poll_close`.
+	//
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), io::Error> >
+	{
+		if self.closed { return Err( io::ErrorKind::NotConnected.into() ).into() }
+
+		if !self.producer.is_full()
+		{
+			Ok(()).into()
+		}
+
+		else
+		{
+			// If the buffer is full, store our waker so readers can wake us up when they have consumed some data.
+			//
+			self.write_waker.replace( cx.waker().clone() );
+
+			Poll::Pending
+		}
+	}
+
+
+	/// Put an item in the buffer. You must call `poll_ready` first and get `Poll::Ready( Ok(()) )`,
+	/// otherwise this will return an error of kind `io::ErrorKind::WouldBlock` when the buffer is full.
+	/// This method returns a `io::ErrorKind::NotConnected` error if called after `poll_close`.
+	//
+	fn start_send( mut self: Pin<&mut Self>, item: T ) -> Result<(), io::Error>
+	{
+		if self.closed { return Err( io::ErrorKind::NotConnected.into() ) }
+
+		if self.producer.push( item ).is_err()
+		{
+			return Err( io::ErrorKind::WouldBlock.into() );
+		}
+
+		// If a reader is waiting for data, now that we wrote, wake them up.
+		//
+		if let Some(waker) = self.read_waker.take()
+		{
+			waker.wake();
+		}
+
+		Ok(())
+	}
+
+
+	/// We are always flushed, this is a noop.
+	/// This method is infallible.
+	//
+	fn poll_flush( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< Result<(), io::Error> >
+	{
+		Ok(()).into()
+	}
+
+
+	/// Closes the sink. After this no more items can be send into it. The stream will yield
+	/// the items still in the buffer and then return `None`.
+	/// This method is infallible.
+	//
+	fn poll_close( mut self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< Result<(), io::Error> >
+	{
+		self.closed = true;
+
+		// If a reader is waiting for data, now that we closed, wake them up.
+		//
+		if let Some(waker) = self.read_waker.take()
+		{
+			waker.wake();
+		}
+
+		Ok(()).into()
+	}
+}
+
+
+
+#[cfg(test)]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ sending to empty buffer
+	// ✔ sending to full buffer sets the waker
+	// ✔ start_send on a full buffer
+	// ✔ the waker from a reader is woken up when closing the sink
+	// ✔ sending to a closed buffer
+	//
+	use crate::{ import::{ *, assert_eq }, RingBuffer };
+	use futures::{ SinkExt };
+
+	#[test]
+	//
+	fn sink() { block_on( async
+	{
+		let mut ring = RingBuffer::<u32>::new(2);
+
+		SinkExt::send( &mut ring, 1 ).await.expect( "send" );
+		SinkExt::send( &mut ring, 2 ).await.expect( "send" );
+
+		assert!( ring.is_full() );
+		assert_eq!( ring.len(), 2 );
+
+		assert!( ring.read_waker .is_none() );
+		assert!( ring.write_waker.is_none() );
+
+		let (waker, _count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( Sink::poll_ready( Pin::new( &mut ring ), &mut cx ).is_pending() );
+		assert!( ring.write_waker.is_some() );
+
+		assert_eq!
+		(
+			Sink::start_send( Pin::new( &mut ring ), 3 ).unwrap_err().kind(),
+			io::ErrorKind::WouldBlock,
+		);
+
+		assert_eq!( 1, ring.consumer.pop().unwrap() );
+		assert_eq!( 2, ring.consumer.pop().unwrap() );
+	})}
+
+
+	#[test]
+	//
+	fn close_wake_reader() { block_on( async
+	{
+		let mut ring = RingBuffer::<u32>::new(2);
+
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( Stream::poll_next( Pin::new( &mut ring ), &mut cx ).is_pending() );
+		assert!( ring.read_waker.is_some() );
+
+		SinkExt::close( &mut ring ).await.expect( "close" );
+
+		assert!( ring.read_waker.is_none() );
+		assert_eq!( count, 1 );
+	})}
+
+
+	#[test]
+	//
+	fn closed_send() { block_on( async
+	{
+		let mut ring = RingBuffer::<u32>::new(2);
+
+		SinkExt::close( &mut ring ).await.expect( "close" );
+
+		assert_eq!( SinkExt::send( &mut ring, 1 ).await.unwrap_err().kind(), io::ErrorKind::NotConnected );
+
+		// Should be the same
+		//
+		assert_eq!( SinkExt::send( &mut ring, 1 ).await.unwrap_err().kind(), io::ErrorKind::NotConnected );
+	})}
+}
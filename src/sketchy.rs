@@ -1,10 +1,49 @@
-use crate::{ import::*, Dictator };
+use crate::{ import::*, Dictator, SketchyConfig, SketchyOp, PartialSize, sketchy_config::WakeKind };
+
+
+/// The questions `Sketchy` asks its [`Dictator`] during one method, so the decisions are logged with the
+/// name of the method without formatting a string on every poll.
+//
+struct Questions
+{
+	fatal    : &'static str ,
+	transient: &'static str ,
+	spurious : &'static str ,
+	pending  : &'static str ,
+	delay    : &'static str ,
+	release  : &'static str ,
+	partial  : &'static str ,
+	size     : &'static str ,
+}
+
+
+/// The [`Questions`] for the method called `$what`.
+//
+macro_rules! questions
+{
+	( $what: literal ) =>
+	{
+		&Questions
+		{
+			fatal    : concat!( $what, " - return fatal error?"       ) ,
+			transient: concat!( $what, " - return transient error?"   ) ,
+			spurious : concat!( $what, " - spurious wake up?"         ) ,
+			pending  : concat!( $what, " - return Pending?"           ) ,
+			delay    : concat!( $what, " - wake up delay in ns"       ) ,
+			release  : concat!( $what, " - release deferred wake up?" ) ,
+			partial  : concat!( $what, " - return Partial?"           ) ,
+			size     : concat!( $what, " - buffer size"               ) ,
+		}
+	};
+}
+
 
 /// A wrapper for any type that implements `AsyncRead`/`AsyncWrite`, that will randomly return pending and
-/// reschedule or only process partial buffers. This helps with testing consumers of these interfaces
-/// on in memory objects like a mock network connection created with [Endpoint](crate::Endpoint) which
-/// would otherwise always be ready. This simulates a more random behavior you might observe on a real
-/// network connection.
+/// reschedule or only process partial buffers. It also implements `Stream`, `Sink` and `Future` when the
+/// wrapped type does, randomly returning pending before polling it. This helps with testing consumers of
+/// these interfaces on in memory objects like a mock network connection created with
+/// [Endpoint](crate::Endpoint) which would otherwise always be ready. This simulates a more random behavior
+/// you might observe on a real network connection.
 ///
 /// The randomness is based on a seed, so that you can reproduce failing tests. In order be reproducible,
 /// your test should be deterministic. In general avoid spawning and executor schedulers, prefer `join!`
@@ -13,54 +52,210 @@ use crate::{ import::*, Dictator };
 /// # Example
 ///
 /// ```
-/// #[test]
+/// use futures_ringbuf::{ Endpoint, Sketchy, Dictator };
+///
+/// // Make sure to log for failing tests, so you can rerun with the same seed.
+/// // futures-ringbuf will log any decisions made by `Sketchy` and will log the
+/// // seed, eg. with: `flexi_logger::Logger::with_str( "trace" ).start()`.
+///
+/// // Since we want to test a random combination of events (pending, partial buffer fills, normal behavior)
+/// // let's run this several times.
 /// //
-/// fn my_test()
+/// for _ in 0..500
 /// {
-///    // Make sure to log for failing tests, so you can rerun with the same seed.
-///    // futures-ringbuf will log any decisions made by `Sketchy` and will log the
-///    // seed.
-///    //
-///    let _ = flexi_logger::Logger::with_str( "trace" ).start();
-///
-///    // Since we want to test a random combination of events (pending, partial buffer fills, normal behavior)
-///    // let's run this several times.
-///    //
-///    for _ in 0..500
-///    {
-///       let seed = Dictator::seed();
-///       let (server, client) = Endpoint::pair( 64, 64 );
-///       let server = Sketchy::new( server, seed );
-///       let client = Sketchy::new( client, seed );
+///    let seed = Dictator::new_seed();
+///    let (server, client) = Endpoint::pair( 64, 64 );
+///    let server = Sketchy::new( server, seed );
+///    let client = Sketchy::new( client, seed );
 ///
 ///
-///       // now use AsyncRead/AsyncWrite on server and client to test your code,
-///       // eg. a codec implementation.
-///    }
+///    // now use AsyncRead/AsyncWrite on server and client to test your code,
+///    // eg. a codec implementation.
 /// }
 /// ```
+///
+/// The probabilities of this behavior can be tuned with a [`SketchyConfig`], see [`Sketchy::with_config`].
+/// The config can also make `AsyncRead`/`AsyncWrite` operations return errors, to test retry logic, and
+/// delay wake ups or wake up the task spuriously, to test code that makes assumptions about wake ups.
+///
+/// With the `tokio` feature, `Sketchy` also implements the tokio versions of `AsyncRead`/`AsyncWrite`
+/// when the wrapped type does.
 //
 #[ derive( Debug ) ]
 //
 pub struct Sketchy<T>
 {
-	inner: T        ,
-	bd   : Dictator ,
+	inner : T                     ,
+	bd    : Dictator              ,
+	config: SketchyConfig         ,
+	broken: Option<io::ErrorKind> ,
 }
 
 
 impl<T> Sketchy<T>
 {
-	/// Create a new wrapper with random behavior based on seed.
+	/// Create a new wrapper with random behavior based on seed, with the [default](SketchyConfig::default)
+	/// probabilities.
 	//
 	pub fn new( inner: T, seed: u64 ) -> Self
+	{
+		Self::with_config( inner, seed, SketchyConfig::default() )
+	}
+
+
+	/// Create a new wrapper with random behavior based on seed, with the probabilities from `config`.
+	//
+	pub fn with_config( inner: T, seed: u64, config: SketchyConfig ) -> Self
 	{
 		Self
 		{
-			inner,
-			bd: Dictator::new( seed )
+			inner                         ,
+			bd    : Dictator::new( seed ) ,
+			config                        ,
+			broken: None                  ,
+		}
+	}
+
+
+	/// Whether a fatal error has been injected, see [`SketchyOp::fatal_error`].
+	//
+	pub fn is_broken( &self ) -> bool
+	{
+		self.broken.is_some()
+	}
+
+
+	/// Decide whether to return an error. Once a fatal error has been returned, this always returns
+	/// an error of the same kind.
+	//
+	fn error( &mut self, q: &Questions, op: SketchyOp ) -> Option<io::Error>
+	{
+		if let Some(kind) = self.broken
+		{
+			return Some( io::Error::new( kind, "futures_ringbuf::Sketchy: broken by an injected fatal error" ) );
+		}
+
+		if let Some((prob, kind)) = op.fatal
+		{
+			if self.bd.please( q.fatal, prob )
+			{
+				self.broken = Some( kind );
+
+				return Some( io::Error::new( kind, "futures_ringbuf::Sketchy: injected fatal error" ) );
+			}
+		}
+
+		if let Some((prob, kind)) = op.transient
+		{
+			if self.bd.please( q.transient, prob )
+			{
+				return Some( io::Error::new( kind, "futures_ringbuf::Sketchy: injected transient error" ) );
+			}
+		}
+
+		None
+	}
+
+
+	/// Decide whether to return pending. If so, the task is woken up according to the [`WakeMode`](crate::WakeMode).
+	//
+	fn pending( &mut self, cx: &mut Context<'_>, q: &Questions, op: SketchyOp ) -> bool
+	{
+		self.release( q );
+
+		// Only ask when configured, so the decisions for a seed don't change when this is not used.
+		//
+		if self.config.spurious > 0.0 && self.bd.please( q.spurious, self.config.spurious )
+		{
+			cx.waker().wake_by_ref();
+		}
+
+		if self.bd.please( q.pending, op.pending )
+		{
+			self.schedule( cx.waker(), q );
+			return true;
+		}
+
+		false
+	}
+
+
+	/// Arrange for the task to be woken up after returning pending.
+	//
+	fn schedule( &mut self, waker: &Waker, q: &Questions )
+	{
+		match &self.config.wake.kind
+		{
+			WakeKind::Immediate => waker.wake_by_ref(),
+
+			WakeKind::Deferred{ wakers, .. } => wakers.push( waker.clone() ),
+
+			WakeKind::Delayed{ clock, max } =>
+			{
+				let max   = max.as_nanos() as u64;
+				let delay = self.bd.pick( q.delay, 0..max.saturating_add( 1 ) );
+
+				clock.wake_at( clock.now() + Duration::from_nanos( delay ), waker.clone() );
+			}
+		}
+	}
+
+
+	/// With [`WakeMode::deferred`](crate::WakeMode::deferred), wake up some of the tasks that are waiting in the queue.
+	//
+	fn release( &mut self, q: &Questions )
+	{
+		let (wakers, release) = match &self.config.wake.kind
+		{
+			WakeKind::Deferred{ wakers, release } => ( wakers.clone(), *release ),
+			_                                     => return,
+		};
+
+		let mut wake = Vec::new();
+
+		for waker in wakers.take()
+		{
+			if self.bd.please( q.release, release )
+			{
+				wake.push( waker );
+			}
+
+			else
+			{
+				wakers.push( waker );
+			}
+		}
+
+		for waker in wake
+		{
+			waker.wake();
 		}
 	}
+
+
+	/// Decide whether to only pass part of a buffer of `len` bytes to the wrapped type, and how much.
+	//
+	fn partial( &mut self, q: &Questions, op: SketchyOp, len: usize ) -> Option<usize>
+	{
+		// Buffer 0 is an error from the caller and buffer 1 means we are not allowed to make it 0,
+		// so no point in running this part.
+		//
+		if len <= 1 || !self.bd.please( q.partial, op.partial )
+		{
+			return None;
+		}
+
+		// It's important we don't allow zero here, since that usually means that the stream has ended.
+		//
+		let size = match op.size
+		{
+			PartialSize::Uniform   => self.bd.pick( q.size, 1..len ),
+			PartialSize::OneByte   => 1,
+			PartialSize::UpTo(max) => self.bd.pick( q.size, 1..max.clamp( 1, len - 1 ) + 1 ),
+		};
+
+		Some( size )
+	}
 }
 
 
@@ -68,26 +263,26 @@ impl<T> AsyncRead for Sketchy<T>
 
 	where T: AsyncRead + Unpin
 {
-	/// About one third of the time, this will return pending and reschedule the waker, one third will
-	/// only pass a partial buffer to the wrapped type and one third will just forward the call unmodified.
+	/// With the default configuration, about one third of the time, this will return pending and reschedule
+	/// the waker, one third will only pass a partial buffer to the wrapped type and one third will just forward
+	/// the call unmodified.
 	//
 	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< Result<usize, io::Error> >
 	{
-		if self.bd.please( "AsyncRead::poll_read - return Pending?", 0.3 )
+		let op = self.config.read;
+
+		if let Some(err) = self.error( questions!( "AsyncRead::poll_read" ), op )
 		{
-			cx.waker().wake_by_ref();
-			return Poll::Pending;
+			return Poll::Ready( Err( err ) );
 		}
 
-		// Buffer 0 is an error from the caller and buffer 1 means we are not allowed to make it 0,
-		// so no point in running this part.
-		//
-		if buf.len() > 1 && self.bd.please( "AsyncRead::poll_read - return Partial?", 0.5 )
+		if self.pending( cx, questions!( "AsyncRead::poll_read" ), op )
 		{
-			// It's important we don't allow zero here, since that usually means that the stream has ended.
-			//
-			let size = self.bd.pick( "AsyncRead::poll_read - buffer size", 1..buf.len() );
+			return Poll::Pending;
+		}
 
+		if let Some(size) = self.partial( questions!( "AsyncRead::poll_read" ), op, buf.len() )
+		{
 			return Pin::new( &mut self.inner ).poll_read( cx, &mut buf[0..size] )
 		}
 
@@ -101,21 +296,20 @@ impl<T> AsyncWrite for Sketchy<T> where T: AsyncWrite + Unpin
 {
 	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
 	{
-		if self.bd.please( "AsyncWrite::poll_write - return Pending?", 0.3 )
+		let op = self.config.write;
+
+		if let Some(err) = self.error( questions!( "AsyncWrite::poll_write" ), op )
 		{
-			cx.waker().wake_by_ref();
-			return Poll::Pending;
+			return Poll::Ready( Err( err ) );
 		}
 
-		// Buffer 0 is an error from the caller and buffer 1 means we are not allowed to make it 0,
-		// so no point in running this part.
-		//
-		if buf.len() > 1 && self.bd.please( "AsyncWrite::poll_write - return Partial?", 0.5 )
+		if self.pending( cx, questions!( "AsyncWrite::poll_write" ), op )
 		{
-			// It's important we don't allow zero here, since that usually means that the stream has ended.
-			//
-			let size = self.bd.pick( "AsyncWrite::poll_write - buffer size", 1..buf.len() );
+			return Poll::Pending;
+		}
 
+		if let Some(size) = self.partial( questions!( "AsyncWrite::poll_write" ), op, buf.len() )
+		{
 			return Pin::new( &mut self.inner ).poll_write( cx, &buf[0..size] )
 		}
 
@@ -126,9 +320,15 @@ impl<T> AsyncWrite for Sketchy<T> where T: AsyncWrite + Unpin
 
 	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll< io::Result<()> >
 	{
-		if self.bd.please( "AsyncWrite::poll_flush - return Pending?", 0.5 )
+		let op = self.config.flush;
+
+		if let Some(err) = self.error( questions!( "AsyncWrite::poll_flush" ), op )
+		{
+			return Poll::Ready( Err( err ) );
+		}
+
+		if self.pending( cx, questions!( "AsyncWrite::poll_flush" ), op )
 		{
-			cx.waker().wake_by_ref();
 			return Poll::Pending;
 		}
 
@@ -139,12 +339,222 @@ impl<T> AsyncWrite for Sketchy<T> where T: AsyncWrite + Unpin
 
 	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
 	{
-		if self.bd.please( "AsyncWrite::poll_close - return Pending?", 0.5 )
+		let op = self.config.close;
+
+		if let Some(err) = self.error( questions!( "AsyncWrite::poll_close" ), op )
+		{
+			return Poll::Ready( Err( err ) );
+		}
+
+		if self.pending( cx, questions!( "AsyncWrite::poll_close" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll_close( cx )
+	}
+}
+
+
+
+impl<T> Stream for Sketchy<T> where T: Stream + Unpin
+{
+	type Item = T::Item;
+
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		let op = self.config.read;
+
+		if self.pending( cx, questions!( "Stream::poll_next" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll_next( cx )
+	}
+
+
+	fn size_hint( &self ) -> (usize, Option<usize>)
+	{
+		self.inner.size_hint()
+	}
+}
+
+
+
+impl<T, Item> Sink<Item> for Sketchy<T> where T: Sink<Item> + Unpin
+{
+	type Error = T::Error;
+
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		let op = self.config.write;
+
+		if self.pending( cx, questions!( "Sink::poll_ready" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll_ready( cx )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: Item ) -> Result<(), Self::Error>
+	{
+		Pin::new( &mut self.inner ).start_send( item )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		let op = self.config.flush;
+
+		if self.pending( cx, questions!( "Sink::poll_flush" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll_flush( cx )
+	}
+
+
+	fn poll_close( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		let op = self.config.close;
+
+		if self.pending( cx, questions!( "Sink::poll_close" ), op )
 		{
-			cx.waker().wake_by_ref();
 			return Poll::Pending;
 		}
 
 		Pin::new( &mut self.inner ).poll_close( cx )
 	}
 }
+
+
+
+impl<T> Future for Sketchy<T> where T: Future + Unpin
+{
+	type Output = T::Output;
+
+	fn poll( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Self::Output >
+	{
+		let op = self.config.poll;
+
+		if self.pending( cx, questions!( "Future::poll" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll( cx )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio" ) ]
+//
+impl<T> tokio::io::AsyncRead for Sketchy<T>
+
+	where T: tokio::io::AsyncRead + Unpin
+{
+	/// Same behavior as the futures version of `AsyncRead`.
+	//
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		let op = self.config.read;
+
+		if let Some(err) = self.error( questions!( "tokio::AsyncRead::poll_read" ), op )
+		{
+			return Poll::Ready( Err( err ) );
+		}
+
+		if self.pending( cx, questions!( "tokio::AsyncRead::poll_read" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		if let Some(size) = self.partial( questions!( "tokio::AsyncRead::poll_read" ), op, buf.remaining() )
+		{
+			let (poll, filled) =
+			{
+				let mut partial = tokio::io::ReadBuf::new( buf.initialize_unfilled_to( size ) );
+				let poll        = Pin::new( &mut self.inner ).poll_read( cx, &mut partial );
+
+				(poll, partial.filled().len())
+			};
+
+			buf.advance( filled );
+
+			return poll;
+		}
+
+		Pin::new( &mut self.inner ).poll_read( cx, buf )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio" ) ]
+//
+impl<T> tokio::io::AsyncWrite for Sketchy<T> where T: tokio::io::AsyncWrite + Unpin
+{
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		let op = self.config.write;
+
+		if let Some(err) = self.error( questions!( "tokio::AsyncWrite::poll_write" ), op )
+		{
+			return Poll::Ready( Err( err ) );
+		}
+
+		if self.pending( cx, questions!( "tokio::AsyncWrite::poll_write" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		if let Some(size) = self.partial( questions!( "tokio::AsyncWrite::poll_write" ), op, buf.len() )
+		{
+			return Pin::new( &mut self.inner ).poll_write( cx, &buf[0..size] )
+		}
+
+
+		Pin::new( &mut self.inner ).poll_write( cx, buf )
+	}
+
+
+	fn poll_flush( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		let op = self.config.flush;
+
+		if let Some(err) = self.error( questions!( "tokio::AsyncWrite::poll_flush" ), op )
+		{
+			return Poll::Ready( Err( err ) );
+		}
+
+		if self.pending( cx, questions!( "tokio::AsyncWrite::poll_flush" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll_flush( cx )
+	}
+
+
+	fn poll_shutdown( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		let op = self.config.close;
+
+		if let Some(err) = self.error( questions!( "tokio::AsyncWrite::poll_shutdown" ), op )
+		{
+			return Poll::Ready( Err( err ) );
+		}
+
+		if self.pending( cx, questions!( "tokio::AsyncWrite::poll_shutdown" ), op )
+		{
+			return Poll::Pending;
+		}
+
+		Pin::new( &mut self.inner ).poll_shutdown( cx )
+	}
+}
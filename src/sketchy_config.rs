@@ -0,0 +1,439 @@
+use crate::{ import::*, Clock, dictator::check_probability };
+
+
+/// How [`Sketchy`](crate::Sketchy) behaves for one operation, like `poll_read`. Set it on a [`SketchyConfig`].
+//
+#[ derive( Debug, Clone, Copy, PartialEq ) ]
+//
+pub struct SketchyOp
+{
+	pub(crate) pending  : f64                          ,
+	pub(crate) partial  : f64                          ,
+	pub(crate) size     : PartialSize                  ,
+	pub(crate) transient: Option<(f64, io::ErrorKind)> ,
+	pub(crate) fatal    : Option<(f64, io::ErrorKind)> ,
+}
+
+
+impl SketchyOp
+{
+	/// Return pending with probability `pending` and, if not, only pass part of the buffer with probability
+	/// `partial`. The size of the partial buffer is uniformly distributed.
+	///
+	/// For operations that don't take a buffer, like `poll_flush` or `Stream::poll_next`, only the pending
+	/// probability is used.
+	///
+	/// # Panics
+	///
+	/// When a probability is not between 0 and 1.
+	//
+	pub fn new( pending: f64, partial: f64 ) -> Self
+	{
+		Self
+		{
+			pending  : check_probability( pending ) ,
+			partial  : check_probability( partial ) ,
+			size     : PartialSize::Uniform         ,
+			transient: None                         ,
+			fatal    : None                         ,
+		}
+	}
+
+
+	/// Never interfere with this operation.
+	//
+	pub fn reliable() -> Self
+	{
+		Self::new( 0.0, 0.0 )
+	}
+
+
+	/// The distribution of the size of partial buffers.
+	//
+	pub fn size( mut self, size: PartialSize ) -> Self
+	{
+		self.size = size;
+		self
+	}
+
+
+	/// Return an error of `kind` with `probability`, after which the stream keeps working, like
+	/// `Interrupted` or `WouldBlock` on a real socket. Only for the `AsyncRead`/`AsyncWrite` operations.
+	///
+	/// # Panics
+	///
+	/// When the probability is not between 0 and 1.
+	//
+	pub fn transient_error( mut self, probability: f64, kind: io::ErrorKind ) -> Self
+	{
+		self.transient = Some(( check_probability( probability ), kind ));
+		self
+	}
+
+
+	/// Return an error of `kind` with `probability`, after which the stream stays broken, like
+	/// `ConnectionReset` on a real socket. All further `AsyncRead`/`AsyncWrite` operations on the
+	/// [`Sketchy`](crate::Sketchy) return an error of the same kind, without calling the wrapped type.
+	///
+	/// # Panics
+	///
+	/// When the probability is not between 0 and 1.
+	//
+	pub fn fatal_error( mut self, probability: f64, kind: io::ErrorKind ) -> Self
+	{
+		self.fatal = Some(( check_probability( probability ), kind ));
+		self
+	}
+
+
+	/// The probability of returning pending.
+	//
+	pub fn pending( &self ) -> f64
+	{
+		self.pending
+	}
+
+
+	/// The probability of only passing part of the buffer.
+	//
+	pub fn partial( &self ) -> f64
+	{
+		self.partial
+	}
+}
+
+
+/// The size of a partial buffer passed on by [`Sketchy`](crate::Sketchy). It's always at least one byte and
+/// less than the full buffer.
+//
+#[ derive( Debug, Clone, Copy, PartialEq, Eq ) ]
+//
+pub enum PartialSize
+{
+	/// Any size is equally likely.
+	//
+	Uniform,
+
+	/// Always a single byte, the worst case for code that assembles frames.
+	//
+	OneByte,
+
+	/// Any size up to this many bytes is equally likely.
+	//
+	UpTo( usize ),
+}
+
+
+
+/// The probabilities of the random behavior of [`Sketchy`](crate::Sketchy) for each operation. Use one of the
+/// presets or configure every operation with a [`SketchyOp`].
+///
+/// ```
+/// use futures_ringbuf::{ SketchyConfig, SketchyOp, PartialSize };
+///
+/// // Mostly partial reads of a few bytes, writes are never disturbed.
+/// //
+/// let config = SketchyConfig::default()
+///
+///    .read ( SketchyOp::new( 0.05, 0.9 ).size( PartialSize::UpTo( 4 ) ) )
+///    .write( SketchyOp::reliable() )
+/// ;
+/// ```
+//
+#[ derive( Debug, Clone ) ]
+//
+pub struct SketchyConfig
+{
+	pub(crate) read    : SketchyOp ,
+	pub(crate) write   : SketchyOp ,
+	pub(crate) flush   : SketchyOp ,
+	pub(crate) close   : SketchyOp ,
+	pub(crate) poll    : SketchyOp ,
+	pub(crate) wake    : WakeMode  ,
+	pub(crate) spurious: f64       ,
+}
+
+
+impl SketchyConfig
+{
+	/// Never interfere, a starting point to only disturb some operations.
+	//
+	pub fn reliable() -> Self
+	{
+		Self
+		{
+			read    : SketchyOp::reliable() ,
+			write   : SketchyOp::reliable() ,
+			flush   : SketchyOp::reliable() ,
+			close   : SketchyOp::reliable() ,
+			poll    : SketchyOp::reliable() ,
+			wake    : WakeMode::immediate() ,
+			spurious: 0.0                   ,
+		}
+	}
+
+
+	/// Reads and writes mostly process partial buffers and are rarely pending. This exercises code that
+	/// has to reassemble or resume on partial data.
+	//
+	pub fn mostly_partial() -> Self
+	{
+		Self
+		{
+			read    : SketchyOp::new( 0.05, 0.9 ) ,
+			write   : SketchyOp::new( 0.05, 0.9 ) ,
+			flush   : SketchyOp::new( 0.05, 0.0 ) ,
+			close   : SketchyOp::new( 0.05, 0.0 ) ,
+			poll    : SketchyOp::new( 0.05, 0.0 ) ,
+			wake    : WakeMode::immediate()       ,
+			spurious: 0.0                         ,
+		}
+	}
+
+
+	/// All operations are pending most of the time. This shakes out code that loses wakeups.
+	//
+	pub fn heavy_pending() -> Self
+	{
+		Self
+		{
+			read    : SketchyOp::new( 0.8, 0.3 ) ,
+			write   : SketchyOp::new( 0.8, 0.3 ) ,
+			flush   : SketchyOp::new( 0.8, 0.0 ) ,
+			close   : SketchyOp::new( 0.8, 0.0 ) ,
+			poll    : SketchyOp::new( 0.8, 0.0 ) ,
+			wake    : WakeMode::immediate()      ,
+			spurious: 0.0                        ,
+		}
+	}
+
+
+	/// The behavior of `AsyncRead::poll_read` and `Stream::poll_next`.
+	//
+	pub fn read( mut self, op: SketchyOp ) -> Self
+	{
+		self.read = op;
+		self
+	}
+
+
+	/// The behavior of `AsyncWrite::poll_write` and `Sink::poll_ready`.
+	//
+	pub fn write( mut self, op: SketchyOp ) -> Self
+	{
+		self.write = op;
+		self
+	}
+
+
+	/// The behavior of `AsyncWrite::poll_flush` and `Sink::poll_flush`.
+	//
+	pub fn flush( mut self, op: SketchyOp ) -> Self
+	{
+		self.flush = op;
+		self
+	}
+
+
+	/// The behavior of `AsyncWrite::poll_close`, or `poll_shutdown` for tokio, and `Sink::poll_close`.
+	//
+	pub fn close( mut self, op: SketchyOp ) -> Self
+	{
+		self.close = op;
+		self
+	}
+
+
+	/// The behavior of `Future::poll`.
+	//
+	pub fn poll( mut self, op: SketchyOp ) -> Self
+	{
+		self.poll = op;
+		self
+	}
+
+
+	/// How the task is woken up after `Sketchy` returns pending. Defaults to [`WakeMode::immediate`].
+	//
+	pub fn wake( mut self, mode: WakeMode ) -> Self
+	{
+		self.wake = mode;
+		self
+	}
+
+
+	/// Wake up the task with `probability` on every poll, without any change in state. Code that assumes
+	/// it is only woken up when it can make progress will misbehave.
+	///
+	/// # Panics
+	///
+	/// When the probability is not between 0 and 1.
+	//
+	pub fn spurious_wakes( mut self, probability: f64 ) -> Self
+	{
+		self.spurious = check_probability( probability );
+		self
+	}
+}
+
+
+impl Default for SketchyConfig
+{
+	/// Reads and writes are pending 30% of the time and partial half of the remaining time. Flush and
+	/// close are pending half of the time and futures 30% of the time. This is what
+	/// [`Sketchy::new`](crate::Sketchy::new) uses.
+	//
+	fn default() -> Self
+	{
+		Self
+		{
+			read    : SketchyOp::new( 0.3, 0.5 ) ,
+			write   : SketchyOp::new( 0.3, 0.5 ) ,
+			flush   : SketchyOp::new( 0.5, 0.0 ) ,
+			close   : SketchyOp::new( 0.5, 0.0 ) ,
+			poll    : SketchyOp::new( 0.3, 0.0 ) ,
+			wake    : WakeMode::immediate()      ,
+			spurious: 0.0                        ,
+		}
+	}
+}
+
+
+
+/// When to wake up a task after [`Sketchy`](crate::Sketchy) returned pending. Set it with [`SketchyConfig::wake`].
+//
+#[ derive( Debug, Clone ) ]
+//
+pub struct WakeMode
+{
+	pub(crate) kind: WakeKind,
+}
+
+
+#[ derive( Debug, Clone ) ]
+//
+pub(crate) enum WakeKind
+{
+	Immediate,
+	Deferred { wakers: DeferredWakes, release: f64 },
+	Delayed { clock: Arc<dyn Clock>, max: Duration },
+}
+
+
+impl WakeMode
+{
+	/// Wake up the task right away.
+	//
+	pub fn immediate() -> Self
+	{
+		Self{ kind: WakeKind::Immediate }
+	}
+
+
+	/// Put the waker in `wakers`. Every later poll of a `Sketchy` that uses the same [`DeferredWakes`]
+	/// wakes up each waiting task with `release_probability`, as decided by the [`Dictator`](crate::Dictator)
+	/// of that `Sketchy`. You can also wake them all with [`DeferredWakes::wake_all`].
+	///
+	/// Make sure something keeps polling or call `wake_all`, otherwise the task hangs. That is the point
+	/// when testing code that wrongly assumes a wake up arrives promptly, but it means that a `Sketchy`
+	/// that is polled alone will hang.
+	///
+	/// # Panics
+	///
+	/// When `release_probability` is not between 0 and 1.
+	//
+	pub fn deferred( wakers: &DeferredWakes, release_probability: f64 ) -> Self
+	{
+		Self{ kind: WakeKind::Deferred { wakers: wakers.clone(), release: check_probability( release_probability ) } }
+	}
+
+
+	/// Wake up the task after a random delay of up to `max`, using `clock`. With a
+	/// [`SystemClock`](crate::SystemClock), the wake up comes from a background thread.
+	//
+	pub fn delayed( clock: impl Clock + 'static, max: Duration ) -> Self
+	{
+		Self{ kind: WakeKind::Delayed { clock: Arc::new( clock ), max } }
+	}
+}
+
+
+impl Default for WakeMode
+{
+	fn default() -> Self
+	{
+		Self::immediate()
+	}
+}
+
+
+
+/// A queue of wakers shared by [`Sketchy`](crate::Sketchy) objects that use [`WakeMode::deferred`]. Clones
+/// share the same queue.
+//
+#[ derive( Debug, Clone, Default ) ]
+//
+pub struct DeferredWakes
+{
+	wakers: Arc< Mutex< Vec<Waker> > >,
+}
+
+
+impl DeferredWakes
+{
+	/// Create an empty queue.
+	//
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+
+	/// The number of tasks waiting for their wake up.
+	//
+	pub fn len( &self ) -> usize
+	{
+		self.lock().len()
+	}
+
+
+	/// Whether no task is waiting for a wake up.
+	//
+	pub fn is_empty( &self ) -> bool
+	{
+		self.lock().is_empty()
+	}
+
+
+	/// Wake up all waiting tasks.
+	//
+	pub fn wake_all( &self )
+	{
+		let wakers = std::mem::take( &mut *self.lock() );
+
+		for waker in wakers
+		{
+			waker.wake();
+		}
+	}
+
+
+	pub(crate) fn push( &self, waker: Waker )
+	{
+		self.lock().push( waker );
+	}
+
+
+	/// Take all waiting wakers out of the queue.
+	//
+	pub(crate) fn take( &self ) -> Vec<Waker>
+	{
+		std::mem::take( &mut *self.lock() )
+	}
+
+
+	fn lock( &self ) -> std::sync::MutexGuard<'_, Vec<Waker>>
+	{
+		self.wakers.lock().unwrap_or_else( PoisonError::into_inner )
+	}
+}
@@ -0,0 +1,933 @@
+use crate::{ import::*, RingBuffer, Producer, Consumer, Clock, async_buf_read::first_slice, latency::*, throttle::Throttle, recorder::Tap };
+
+#[ cfg( feature = "sketchy" ) ] use crate::corruption::Corruptor;
+
+
+/// State shared between both halves of a split RingBuffer. The producer and consumer of the ringbuffer
+/// are already independent, so all we need to share is the wakers, whether the writer has closed and
+/// whether the reader is still around. Everything in here is lock free.
+//
+#[ derive( Debug, Default ) ]
+//
+pub(crate) struct Shared
+{
+	read_waker : AtomicWaker ,
+	write_waker: AtomicWaker ,
+	closed     : AtomicBool  ,
+	reader_gone: AtomicBool  ,
+	reset      : AtomicBool  ,
+}
+
+
+impl Shared
+{
+	/// Abort this direction. All further operations on both halves will fail with
+	/// `io::ErrorKind::ConnectionReset` and pending tasks are woken up. The buffer is owned by the halves,
+	/// so the data in it is only discarded when the reader checks for data.
+	//
+	pub(crate) fn reset( &self )
+	{
+		self.reset.store( true, Ordering::Release );
+
+		self.read_waker .wake();
+		self.write_waker.wake();
+	}
+
+
+	pub(crate) fn check_reset( &self ) -> io::Result<()>
+	{
+		if self.reset.load( Ordering::Acquire ) { Err( io::ErrorKind::ConnectionReset.into() ) }
+		else                                    { Ok(())                                       }
+	}
+}
+
+
+/// The reading half of a RingBuffer, obtained with [`RingBuffer::split`]. It owns the consumer side
+/// of the ringbuffer, so it can be moved to another task than the [`WriteHalf`] and no locking is
+/// needed to read from it.
+///
+/// Implements `AsyncRead` and `AsyncBufRead` when `T` is `u8` and `Stream` for any `T`. Dropping the `ReadHalf`
+/// makes all further writes fail with `io::ErrorKind::BrokenPipe`, like a pipe or TCP connection would.
+//
+pub struct ReadHalf<T: Sized + Copy>
+{
+	consumer: Consumer<T>           ,
+	shared  : Arc<Shared>           ,
+	latency : Option<LatencyReader> ,
+}
+
+
+/// The writing half of a RingBuffer, obtained with [`RingBuffer::split`]. It owns the producer side
+/// of the ringbuffer, so it can be moved to another task than the [`ReadHalf`] and no locking is
+/// needed to write to it.
+///
+/// Implements `AsyncWrite` when `T` is `u8` and `Sink` for any `T`. Dropping the `WriteHalf` closes it,
+/// so the reader will see the end of the stream once it has consumed the remaining data.
+//
+pub struct WriteHalf<T: Sized + Copy>
+{
+	producer: Producer<T>           ,
+	shared  : Arc<Shared>           ,
+	latency : Option<LatencyWriter> ,
+	throttle: Option<Throttle>      ,
+	tap     : Option<Tap>           ,
+
+	#[ cfg( feature = "sketchy" ) ]
+	//
+	corruptor: Option<Corruptor>,
+}
+
+
+impl<T: Sized + Copy> RingBuffer<T>
+{
+	/// Split into a reading and a writing half, each owning their side of the ringbuffer. Contrary
+	/// to `AsyncReadExt::split`, this doesn't put the buffer behind a lock, and the reader will be
+	/// notified when the writer is dropped.
+	///
+	/// Any wakers stored and the closed state of the buffer are carried over.
+	///
+	/// This shadows `AsyncReadExt::split`, which returns different types. Call it as
+	/// `AsyncReadExt::split( ring )` if you need the `futures::io` halves.
+	//
+	pub fn split( self ) -> (ReadHalf<T>, WriteHalf<T>)
+	{
+		self.split_with_latency( None )
+	}
+
+
+	/// Split, where data only becomes readable after `delay` according to `clock`.
+	//
+	pub(crate) fn split_with_latency( self, latency: Option<(Duration, Arc<dyn Clock>)> ) -> (ReadHalf<T>, WriteHalf<T>)
+	{
+		let (lat_reader, lat_writer) = match latency
+		{
+			Some((delay, clock)) =>
+			{
+				let (r, w) = crate::latency::latency( self.capacity(), delay, clock );
+				(Some(r), Some(w))
+			}
+
+			None => (None, None),
+		};
+
+		let shared = Shared::default();
+
+		if let Some(waker) = &self.read_waker  { shared.read_waker .register( waker ) }
+		if let Some(waker) = &self.write_waker { shared.write_waker.register( waker ) }
+
+		shared.closed.store( self.closed, Ordering::Release );
+
+		let shared = Arc::new( shared );
+
+		(
+			ReadHalf { consumer: self.consumer, shared: shared.clone(), latency: lat_reader },
+			WriteHalf{ producer: self.producer, shared                , latency: lat_writer, throttle: None, tap: None,
+
+				#[ cfg( feature = "sketchy" ) ]
+				//
+				corruptor: None,
+			},
+		)
+	}
+}
+
+
+impl<T: Sized + Copy> ReadHalf<T>
+{
+	/// The total capacity of the buffer
+	//
+	pub fn capacity( &self ) -> usize
+	{
+		self.consumer.capacity()
+	}
+
+
+	/// The length of the data in the buffer.
+	//
+	pub fn len( &self ) -> usize
+	{
+		self.consumer.len()
+	}
+
+
+	/// Whether there is no data at all in the buffer.
+	//
+	pub fn is_empty( &self ) -> bool
+	{
+		self.consumer.is_empty()
+	}
+
+
+	/// Whether the writer has been closed or dropped. There might still be data in the buffer.
+	//
+	pub fn is_closed( &self ) -> bool
+	{
+		self.shared.closed.load( Ordering::Acquire )
+	}
+
+
+	/// Whether the given `WriteHalf` is the other half of this buffer.
+	//
+	pub fn is_pair_of( &self, other: &WriteHalf<T> ) -> bool
+	{
+		Arc::ptr_eq( &self.shared, &other.shared )
+	}
+
+
+	pub(crate) fn shared( &self ) -> &Arc<Shared>
+	{
+		&self.shared
+	}
+
+
+	/// Stop reading. The data in the buffer is discarded, further writes will fail with
+	/// `io::ErrorKind::BrokenPipe` and further reads will return end of stream.
+	//
+	pub(crate) fn shutdown( &mut self )
+	{
+		self.shared.reader_gone.store( true, Ordering::Release );
+		self.consumer.clear();
+		self.shared.write_waker.wake();
+	}
+
+
+	/// Take up to `dst.len()` elements that are in the buffer right now, without waiting.
+	//
+	pub(crate) fn read_now( &mut self, dst: &mut [T] ) -> usize
+	{
+		let read = self.consumer.pop_slice( dst );
+
+		if read != 0
+		{
+			self.did_read( read );
+		}
+
+		read
+	}
+
+
+	/// Returns `Some(Ok(true))` if there is data, `Some(Ok(false))` if the stream has ended and `None` if
+	/// we have to wait.
+	//
+	fn check_data( &mut self ) -> Option< io::Result<bool> >
+	{
+		// The connection has been reset, nobody is interested in the data anymore.
+		//
+		if let Err(e) = self.shared.check_reset()
+		{
+			self.consumer.clear();
+			return Some( Err(e) );
+		}
+
+		// We have shut down reading.
+		//
+		if self.shared.reader_gone.load( Ordering::Relaxed )
+		{
+			return Some( Ok(false) );
+		}
+
+		// Load closed before checking the buffer, otherwise the writer could write and close
+		// in between and we would report end of stream while there is still data.
+		//
+		let closed = self.is_closed();
+
+		if      self.readable() != 0               { Some( Ok(true ) ) }
+		else if self.consumer.is_empty() && closed { Some( Ok(false) ) }
+		else                                       { None              }
+	}
+
+
+	/// How much data can be read right now. With latency, this can be less than what is in the buffer.
+	//
+	fn readable( &mut self ) -> usize
+	{
+		match &mut self.latency
+		{
+			Some(latency) => latency.available().min( self.consumer.len() ),
+			None          => self.consumer.len(),
+		}
+	}
+
+
+	/// Record that `amount` elements were taken out of the buffer and wake up the writer.
+	//
+	fn did_read( &mut self, amount: usize )
+	{
+		if let Some(latency) = &mut self.latency
+		{
+			latency.read( amount );
+		}
+
+		self.shared.write_waker.wake();
+	}
+
+
+	/// Wait until there is data in the buffer or the writer is closed. Returns whether there is data.
+	//
+	fn poll_data( &mut self, cx: &mut Context<'_> ) -> Poll< io::Result<bool> >
+	{
+		if let Some(data) = self.check_data()
+		{
+			return Poll::Ready( data );
+		}
+
+		self.shared.read_waker.register( cx.waker() );
+
+		// Data in the buffer might become readable later.
+		//
+		if let Some(latency) = &mut self.latency
+		{
+			latency.wake_when_ready( cx.waker() );
+		}
+
+		// The writer might have written or closed before we registered our waker.
+		//
+		match self.check_data()
+		{
+			Some(data) => Poll::Ready( data ),
+			None       => Poll::Pending      ,
+		}
+	}
+}
+
+
+impl<T: Sized + Copy> WriteHalf<T>
+{
+	/// The total capacity of the buffer
+	//
+	pub fn capacity( &self ) -> usize
+	{
+		self.producer.capacity()
+	}
+
+
+	/// The length of the data in the buffer.
+	//
+	pub fn len( &self ) -> usize
+	{
+		self.producer.len()
+	}
+
+
+	/// Whether there is no data at all in the buffer.
+	//
+	pub fn is_empty( &self ) -> bool
+	{
+		self.producer.is_empty()
+	}
+
+
+	/// Whether the buffer is completely full.
+	//
+	pub fn is_full( &self ) -> bool
+	{
+		self.producer.is_full()
+	}
+
+
+	/// How much free space there is left in the container. On empty, `remaining == capacity`
+	//
+	pub fn remaining( &self ) -> usize
+	{
+		self.producer.free_len()
+	}
+
+
+	/// Whether the given `ReadHalf` is the other half of this buffer.
+	//
+	pub fn is_pair_of( &self, other: &ReadHalf<T> ) -> bool
+	{
+		other.is_pair_of( self )
+	}
+
+
+	/// Whether the reader has been dropped. Writes will fail with `io::ErrorKind::BrokenPipe`.
+	//
+	pub fn is_broken( &self ) -> bool
+	{
+		self.shared.reader_gone.load( Ordering::Acquire )
+	}
+
+
+	/// Returns `None` if we have to wait for place in the buffer.
+	//
+	fn check_space( &self ) -> Option< io::Result<()> >
+	{
+		if let Err(e) = self.shared.check_reset()
+		{
+			return Some( Err(e) );
+		}
+
+		if self.shared.closed.load( Ordering::Relaxed )
+		{
+			return Some( Err( io::ErrorKind::NotConnected.into() ) );
+		}
+
+		if self.is_broken()
+		{
+			return Some( Err( io::ErrorKind::BrokenPipe.into() ) );
+		}
+
+		if !self.producer.is_full() { Some( Ok(()) ) }
+		else                        { None           }
+	}
+
+
+	/// Put as much of `src` in the buffer as fits right now, without waiting.
+	//
+	pub(crate) fn write_now( &mut self, src: &[T] ) -> io::Result<usize>
+	{
+		if let Some( Err(e) ) = self.check_space() { return Err(e) }
+
+		let wrote = self.producer.push_slice( src );
+
+		self.did_write( wrote );
+
+		Ok( wrote )
+	}
+
+
+	/// Wait until there is place in the buffer.
+	//
+	fn poll_space( &self, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		if let Some(result) = self.check_space()
+		{
+			return result.into();
+		}
+
+		self.shared.write_waker.register( cx.waker() );
+
+		// The reader might have made place or have been dropped before we registered our waker.
+		//
+		match self.check_space()
+		{
+			Some(result) => result.into() ,
+			None         => Poll::Pending ,
+		}
+	}
+
+
+	pub(crate) fn shared( &self ) -> &Arc<Shared>
+	{
+		&self.shared
+	}
+
+
+	/// Limit the rate at which data can be written.
+	//
+	pub(crate) fn with_throttle( mut self, throttle: Throttle ) -> Self
+	{
+		self.throttle = Some( throttle );
+		self
+	}
+
+
+	/// Record all data written.
+	//
+	pub(crate) fn with_tap( mut self, tap: Tap ) -> Self
+	{
+		self.tap = Some( tap );
+		self
+	}
+
+
+	/// Corrupt the data written.
+	//
+	#[ cfg( feature = "sketchy" ) ]
+	//
+	pub(crate) fn with_corruptor( mut self, corruptor: Corruptor ) -> Self
+	{
+		self.corruptor = Some( corruptor );
+		self
+	}
+
+
+	/// How many elements the bandwidth limit allows us to write right now.
+	//
+	fn poll_throttle( &mut self, cx: &mut Context<'_> ) -> Poll<usize>
+	{
+		match &mut self.throttle
+		{
+			Some(throttle) => throttle.poll_allowed( cx ) ,
+			None           => Poll::Ready( usize::MAX )   ,
+		}
+	}
+
+
+	/// Record that `amount` elements were put in the buffer and wake up the reader.
+	//
+	fn did_write( &mut self, amount: usize )
+	{
+		if let Some(latency) = &mut self.latency
+		{
+			latency.wrote( amount );
+		}
+
+		if let Some(throttle) = &mut self.throttle
+		{
+			throttle.wrote( amount );
+		}
+
+		self.shared.read_waker.wake();
+	}
+
+
+	pub(crate) fn close_writer( &self )
+	{
+		self.shared.closed.store( true, Ordering::Release );
+		self.shared.read_waker.wake();
+	}
+}
+
+
+impl AsyncRead for ReadHalf<u8>
+{
+	/// Will return Poll::Pending when the buffer is empty. Will be woken up by the [`WriteHalf`] when new
+	/// data is written or the writer is closed or dropped.
+	///
+	/// When the writer is closed and the buffer empty, or if you pass in a 0 byte buffer,
+	/// this will return `Poll::Ready( Ok(0) )`.
+	///
+	/// This method only fails with `io::ErrorKind::ConnectionReset` when the connection has been reset.
+	//
+	fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, dst: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		if dst.is_empty()
+		{
+			return Poll::Ready( Ok(0) );
+		}
+
+		if !ready!( self.poll_data( cx ) )?
+		{
+			return Poll::Ready( Ok(0) );
+		}
+
+		let max  = self.readable().min( dst.len() );
+		let read = self.consumer.pop_slice( &mut dst[..max] );
+
+		self.did_read( read );
+
+		Poll::Ready( Ok(read) )
+	}
+}
+
+
+impl AsyncBufRead for ReadHalf<u8>
+{
+	/// Returns the contiguous readable part of the underlying ringbuffer without copying.
+	/// See the implementation on [`RingBuffer`] for details.
+	//
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		let this = self.get_mut();
+
+		if !ready!( this.poll_data( cx ) )?
+		{
+			return Poll::Ready( Ok(&[]) );
+		}
+
+		// The writer never touches data that is in the buffer, so it's fine to lend it out.
+		//
+		let max   = this.readable();
+		let slice = first_slice( &this.consumer );
+
+		Poll::Ready( Ok( &slice[ ..max.min( slice.len() ) ] ) )
+	}
+
+
+	fn consume( mut self: Pin<&mut Self>, amt: usize )
+	{
+		let skipped = self.consumer.skip( amt );
+
+		if skipped != 0
+		{
+			self.did_read( skipped );
+		}
+	}
+}
+
+
+impl<T: Sized + Copy + Unpin> Stream for ReadHalf<T>
+{
+	type Item = T;
+
+	/// Will return Poll::Pending when the buffer is empty and `None` when the writer is closed
+	/// and the buffer empty or when the connection has been reset.
+	//
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<T> >
+	{
+		if !matches!( ready!( self.poll_data( cx ) ), Ok(true) )
+		{
+			return Poll::Ready( None );
+		}
+
+		let item = self.consumer.pop();
+
+		self.did_read( 1 );
+
+		Poll::Ready( item )
+	}
+}
+
+
+#[ cfg( feature = "sketchy" ) ]
+//
+impl WriteHalf<u8>
+{
+	/// Write what fits of `src`, corrupted. The writer is told all of it was written, even if the end got lost.
+	//
+	fn write_corrupted( &mut self, src: &[u8] ) -> io::Result<usize>
+	{
+		let mut data = src[ ..src.len().min( self.producer.free_len() ) ].to_vec();
+		let accepted = data.len();
+
+		if let Some(tap) = &self.tap
+		{
+			if accepted > 0 { tap.record( &data ) }
+		}
+
+		if let Some(corruptor) = &mut self.corruptor
+		{
+			corruptor.corrupt( &mut data );
+		}
+
+		let wrote = self.producer.push_slice( &data );
+
+		self.did_write( wrote );
+
+		// The lost bytes still count for the bandwidth.
+		//
+		if let Some(throttle) = &mut self.throttle
+		{
+			throttle.wrote( accepted - wrote );
+		}
+
+		Ok( accepted )
+	}
+}
+
+
+impl AsyncWrite for WriteHalf<u8>
+{
+	/// Will return Poll::Pending when the buffer is full. The [`ReadHalf`] will wake up this task
+	/// when new place is made.
+	/// This method returns a `io::ErrorKind::NotConnected` error if called after `poll_close` and
+	/// a `io::ErrorKind::BrokenPipe` error when the reader has been dropped.
+	//
+	fn poll_write( mut self: Pin<&mut Self>, cx: &mut Context<'_>, src: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		ready!( self.poll_space( cx ) )?;
+
+		let allowed = ready!( self.poll_throttle( cx ) ).min( src.len() );
+
+		#[ cfg( feature = "sketchy" ) ]
+		//
+		if self.corruptor.is_some()
+		{
+			return self.write_corrupted( &src[ ..allowed ] ).into();
+		}
+
+		let wrote = self.producer.push_slice( &src[ ..allowed ] );
+
+		if let Some(tap) = &self.tap
+		{
+			if wrote > 0 { tap.record( &src[ ..wrote ] ) }
+		}
+
+		self.did_write( wrote );
+
+		Ok(wrote).into()
+	}
+
+
+	/// We are always flushed, this is a noop.
+	/// This method only fails with `io::ErrorKind::ConnectionReset` when the connection has been reset.
+	//
+	fn poll_flush( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.shared.check_reset().into()
+	}
+
+
+	/// Closes the stream. After this no more data can be send into it.
+	/// This method only fails with `io::ErrorKind::ConnectionReset` when the connection has been reset.
+	//
+	fn poll_close( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.shared.check_reset()?;
+		self.close_writer();
+
+		Ok(()).into()
+	}
+}
+
+
+impl<T: Sized + Copy + Unpin> Sink<T> for WriteHalf<T>
+{
+	type Error = io::Error;
+
+	/// Will return Poll::Pending when the buffer is full.
+	/// This method returns a `io::ErrorKind::NotConnected` error if called after `poll_close` and
+	/// a `io::ErrorKind::BrokenPipe` error when the reader has been dropped.
+	//
+	fn poll_ready( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		ready!( self.poll_space( cx ) )?;
+		ready!( self.poll_throttle( cx ) );
+
+		Ok(()).into()
+	}
+
+
+	/// Put an item in the buffer. Returns `io::ErrorKind::WouldBlock` if you didn't wait for `poll_ready`.
+	//
+	fn start_send( mut self: Pin<&mut Self>, item: T ) -> io::Result<()>
+	{
+		if let Some( Err(e) ) = self.check_space() { return Err(e) }
+
+		if self.producer.push( item ).is_err()
+		{
+			return Err( io::ErrorKind::WouldBlock.into() );
+		}
+
+		self.did_write( 1 );
+
+		Ok(())
+	}
+
+
+	/// We are always flushed, this is a noop.
+	//
+	fn poll_flush( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.shared.check_reset().into()
+	}
+
+
+	/// Closes the sink. The reader will get the remaining items and then `None`.
+	//
+	fn poll_close( self: Pin<&mut Self>, _cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.shared.check_reset()?;
+		self.close_writer();
+
+		Ok(()).into()
+	}
+}
+
+
+/// Makes sure that the reader is notified (woken up if pending) of the end of the stream.
+//
+impl<T: Sized + Copy> Drop for WriteHalf<T>
+{
+	fn drop( &mut self )
+	{
+		self.close_writer();
+	}
+}
+
+
+/// Makes sure that a writer is notified (woken up if pending) that nobody will read the data.
+//
+impl<T: Sized + Copy> Drop for ReadHalf<T>
+{
+	fn drop( &mut self )
+	{
+		self.shared.reader_gone.store( true, Ordering::Release );
+		self.shared.write_waker.wake();
+	}
+}
+
+
+impl<T: Sized + Copy> fmt::Debug for ReadHalf<T>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "ReadHalf with capacity: {}", self.capacity() )
+	}
+}
+
+
+impl<T: Sized + Copy> fmt::Debug for WriteHalf<T>
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		write!( f, "WriteHalf with capacity: {}", self.capacity() )
+	}
+}
+
+
+
+#[cfg(test)]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ reading and writing through the halves
+	// ✔ state carried over from the RingBuffer
+	// ✔ pending reader woken up by a write
+	// ✔ pending writer woken up by a read
+	// ✔ dropping the writer ends the stream and wakes the reader
+	// ✔ dropping the reader breaks the pipe and wakes the writer
+	// ✔ Stream/Sink on the halves
+	// ✔ moving the halves to different threads
+	//
+	use crate::{ import::{ *, assert_eq }, RingBuffer };
+	use futures::{ SinkExt, StreamExt };
+
+
+	#[test]
+	//
+	fn read_write() { block_on( async
+	{
+		let (mut reader, mut writer) = RingBuffer::<u8>::new(2).split();
+		let mut read_buf = [0u8;2];
+
+		assert!( reader.is_pair_of( &writer ) );
+		assert!( writer.is_pair_of( &reader ) );
+
+		writer.write_all( b"ab" ).await.expect( "write" );
+
+		assert!( writer.is_full() );
+		assert_eq!( writer.remaining(), 0 );
+
+
+		// full, writer should be pending
+		//
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( AsyncWrite::poll_write( Pin::new( &mut writer ), &mut cx, b"c" ).is_pending() );
+
+		assert_eq!( 2, reader.read( &mut read_buf ).await.expect( "read" ) );
+		assert_eq!( &read_buf, b"ab" );
+		assert_eq!( count, 1 );
+
+
+		// empty, reader should be pending
+		//
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( AsyncRead::poll_read( Pin::new( &mut reader ), &mut cx, &mut read_buf ).is_pending() );
+
+		writer.write_all( b"c" ).await.expect( "write" );
+		assert_eq!( count, 1 );
+
+		assert_eq!( 1, reader.read( &mut read_buf ).await.expect( "read" ) );
+		assert_eq!( read_buf[0], b'c' );
+	})}
+
+
+	#[test]
+	//
+	fn carry_over() { block_on( async
+	{
+		let mut ring = RingBuffer::<u8>::new(4);
+
+		ring.write_all( b"ab" ).await.expect( "write" );
+		AsyncWriteExt::close( &mut ring ).await.expect( "close" );
+
+		let (mut reader, mut writer) = ring.split();
+		let mut read = Vec::new();
+
+		assert!( reader.is_closed() );
+		assert_eq!( writer.write( b"c" ).await.unwrap_err().kind(), io::ErrorKind::NotConnected );
+
+		reader.read_to_end( &mut read ).await.expect( "read" );
+		assert_eq!( read, b"ab" );
+	})}
+
+
+	#[test]
+	//
+	fn drop_writer() { block_on( async
+	{
+		let (mut reader, mut writer) = RingBuffer::<u8>::new(4).split();
+		let mut read_buf = [0u8;4];
+
+		writer.write_all( b"ab" ).await.expect( "write" );
+
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert_eq!( 2, reader.read( &mut read_buf ).await.expect( "read" ) );
+		assert!( AsyncRead::poll_read( Pin::new( &mut reader ), &mut cx, &mut read_buf ).is_pending() );
+
+		drop( writer );
+
+		assert_eq!( count, 1 );
+		assert_eq!( 0, reader.read( &mut read_buf ).await.expect( "read" ) );
+	})}
+
+
+	#[test]
+	//
+	fn drop_reader() { block_on( async
+	{
+		let (reader, mut writer) = RingBuffer::<u8>::new(2).split();
+
+		writer.write_all( b"ab" ).await.expect( "write" );
+
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( AsyncWrite::poll_write( Pin::new( &mut writer ), &mut cx, b"c" ).is_pending() );
+		assert!( !writer.is_broken() );
+
+		drop( reader );
+
+		assert_eq!( count, 1 );
+		assert!( writer.is_broken() );
+		assert_eq!( writer.write( b"c" ).await.unwrap_err().kind(), io::ErrorKind::BrokenPipe );
+
+		// Should be the same
+		//
+		assert_eq!( writer.write( b"c" ).await.unwrap_err().kind(), io::ErrorKind::BrokenPipe );
+	})}
+
+
+	#[test]
+	//
+	fn stream_sink() { block_on( async
+	{
+		let (reader, mut writer) = RingBuffer::<u32>::new(2).split();
+
+		let send = async move
+		{
+			for i in 0..10
+			{
+				writer.send( i ).await.expect( "send" );
+			}
+		};
+
+		let (received, _) = futures::join!( reader.collect::<Vec<_>>(), send );
+
+		assert_eq!( received, (0..10).collect::<Vec<_>>() );
+	})}
+
+
+	#[test]
+	//
+	fn threads()
+	{
+		let (mut reader, mut writer) = RingBuffer::<u8>::new(7).split();
+
+		let send = std::thread::spawn( move || block_on( async move
+		{
+			for i in 0..=255u8
+			{
+				writer.write_all( &[i] ).await.expect( "write" );
+			}
+		}));
+
+		let mut read = Vec::new();
+
+		block_on( reader.read_to_end( &mut read ) ).expect( "read" );
+		send.join().expect( "join writer thread" );
+
+		assert_eq!( read, (0..=255u8).collect::<Vec<_>>() );
+	}
+}
@@ -0,0 +1,141 @@
+use crate::{ import::*, RingBuffer };
+
+
+impl<T: Sized + Copy + Unpin> Stream for RingBuffer<T>
+{
+	type Item = T;
+
+	/// Will return Poll::Pending when the buffer is empty. Will be woken up by the Sink impl when a new
+	/// item is sent or the sink is closed.
+	///
+	/// When the buffer is closed and empty, this will return `Poll::Ready( None )`.
+	//
+	fn poll_next( mut self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<T> >
+	{
+		if let Some(item) = self.consumer.pop()
+		{
+			// If a writer is waiting for place in the buffer, wake them.
+			//
+			if let Some(waker) = self.write_waker.take()
+			{
+				waker.wake();
+			}
+
+			Poll::Ready( Some(item) )
+		}
+
+		else if self.closed
+		{
+			// Signals end of stream.
+			//
+			Poll::Ready( None )
+		}
+
+		else
+		{
+			// Store this waker so that the writer can wake us up after they sent something.
+			//
+			self.read_waker.replace( cx.waker().clone() );
+
+			Poll::Pending
+		}
+	}
+
+
+	fn size_hint( &self ) -> (usize, Option<usize>)
+	{
+		if self.closed { (self.len(), Some( self.len() )) }
+		else           { (self.len(), None              ) }
+	}
+}
+
+
+
+#[cfg(test)]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ reading items from a full buffer
+	// ✔ reading from empty buffer sets the waker
+	// ✔ the waker being woken up by a send
+	// ✔ the writer being woken up when an item is taken out
+	// ✔ end of stream on closed and empty buffer
+	//
+	use crate::{ import::{ *, assert_eq }, RingBuffer };
+	use futures::{ SinkExt, StreamExt };
+
+	#[test]
+	//
+	fn stream() { block_on( async
+	{
+		let mut ring = RingBuffer::<u32>::new(2);
+
+		ring.producer.push( 1 ).expect( "write" );
+		ring.producer.push( 2 ).expect( "write" );
+
+		assert_eq!( Some(1), ring.next().await );
+		assert_eq!( Some(2), ring.next().await );
+
+		assert!( ring.read_waker .is_none() );
+		assert!( ring.write_waker.is_none() );
+
+
+		// Empty buffer
+		//
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( Stream::poll_next( Pin::new( &mut ring ), &mut cx ).is_pending() );
+		assert!( ring.read_waker.is_some() );
+
+		SinkExt::send( &mut ring, 3 ).await.expect( "send" );
+
+		assert!( ring.read_waker.is_none() );
+		assert_eq!( count, 1 );
+
+		assert_eq!( Some(3), ring.next().await );
+	})}
+
+
+	#[test]
+	//
+	fn wake_writer() { block_on( async
+	{
+		let mut ring = RingBuffer::<u32>::new(1);
+
+		SinkExt::send( &mut ring, 1 ).await.expect( "send" );
+
+		let (waker, count) = new_count_waker();
+		let mut cx = Context::from_waker( &waker );
+
+		assert!( Sink::poll_ready( Pin::new( &mut ring ), &mut cx ).is_pending() );
+		assert!( ring.write_waker.is_some() );
+
+		assert_eq!( Some(1), ring.next().await );
+
+		assert!( ring.write_waker.is_none() );
+		assert_eq!( count, 1 );
+	})}
+
+
+	#[test]
+	//
+	fn closed_stream() { block_on( async
+	{
+		let mut ring = RingBuffer::<u32>::new(2);
+
+		SinkExt::send ( &mut ring, 1 ).await.expect( "send"  );
+		SinkExt::close( &mut ring    ).await.expect( "close" );
+
+		assert_eq!( ring.size_hint(), (1, Some(1)) );
+
+		assert_eq!( Some(1), ring.next().await );
+		assert_eq!( None   , ring.next().await );
+
+		// try again, just in case
+		//
+		assert_eq!( None, ring.next().await );
+	})}
+}
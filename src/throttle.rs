@@ -0,0 +1,99 @@
+use crate::{ import::*, Clock };
+
+
+/// A token bucket that limits how fast data can be written. The bucket holds up to `burst` tokens and
+/// is refilled at `rate` tokens per second. Writing an element takes a token.
+//
+pub(crate) struct Throttle
+{
+	rate  : f64            ,
+	burst : f64            ,
+	tokens: f64            ,
+	last  : Duration       ,
+	clock : Arc<dyn Clock> ,
+
+	// The wake up we asked the clock for, so we don't ask again every time we are polled.
+	//
+	timer: Option<(Duration, Waker)>,
+}
+
+
+impl Throttle
+{
+	/// The bucket starts full.
+	//
+	pub(crate) fn new( rate: u64, burst: u64, clock: Arc<dyn Clock> ) -> Self
+	{
+		assert!( rate  > 0, "futures_ringbuf: the bandwidth must be at least 1 per second" );
+		assert!( burst > 0, "futures_ringbuf: the burst size must be at least 1"           );
+
+		Self
+		{
+			rate  : rate  as f64 ,
+			burst : burst as f64 ,
+			tokens: burst as f64 ,
+			last  : clock.now()  ,
+			timer : None         ,
+			clock                ,
+		}
+	}
+
+
+	/// How many elements may be written right now. If none, `waker` will be woken up when the
+	/// next one may be written.
+	//
+	pub(crate) fn poll_allowed( &mut self, cx: &mut Context<'_> ) -> Poll<usize>
+	{
+		let now = self.clock.now();
+
+		self.tokens = ( self.tokens + ( now - self.last ).as_secs_f64() * self.rate ).min( self.burst );
+		self.last   = now;
+
+		if self.tokens >= 1.0
+		{
+			return Poll::Ready( self.tokens as usize );
+		}
+
+		let wait = Duration::from_secs_f64( ( 1.0 - self.tokens ) / self.rate );
+
+		// Tokens only come back over time, so a timer that hasn't fired yet is not too late.
+		//
+		if let Some( (deadline, registered) ) = &self.timer
+		{
+			if *deadline > now && registered.will_wake( cx.waker() )
+			{
+				return Poll::Pending;
+			}
+		}
+
+		log::trace!( "throttle: bandwidth exceeded, waiting {:?}", wait );
+
+		self.clock.wake_at( now + wait, cx.waker().clone() );
+		self.timer = Some(( now + wait, cx.waker().clone() ));
+
+		Poll::Pending
+	}
+
+
+	/// Record that `amount` elements have been written.
+	//
+	pub(crate) fn wrote( &mut self, amount: usize )
+	{
+		self.tokens -= amount as f64;
+	}
+}
+
+
+impl fmt::Debug for Throttle
+{
+	fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result
+	{
+		f.debug_struct( "Throttle" )
+
+			.field( "rate"  , &self.rate   )
+			.field( "burst" , &self.burst  )
+			.field( "tokens", &self.tokens )
+			.field( "clock" , &self.clock  )
+			.finish()
+	}
+}
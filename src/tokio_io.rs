@@ -0,0 +1,195 @@
+//! Native implementations of the tokio `AsyncRead`/`AsyncWrite` traits. They forward to the futures
+//! implementations, so behavior is identical to going through `tokio_util::compat`, but you keep
+//! access to the methods of the types themselves.
+//
+use crate::{ import::*, RingBuffer, Endpoint, ReadHalf, WriteHalf, MockStream };
+use tokio::io::{ AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf };
+
+
+/// Forward a tokio `poll_read` to a futures `AsyncRead` implementation.
+//
+pub(crate) fn poll_read_buf<R>( reader: Pin<&mut R>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll< io::Result<()> >
+
+	where R: AsyncRead + ?Sized
+{
+	let n = futures::ready!( reader.poll_read( cx, buf.initialize_unfilled() ) )?;
+
+	buf.advance( n );
+
+	Ok(()).into()
+}
+
+
+impl TokioAsyncRead for RingBuffer<u8>
+{
+	/// Behaves like the futures `AsyncRead` implementation. When the buffer is closed and empty,
+	/// this returns `Ok(())` without filling `buf`, which signals end of stream.
+	//
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		poll_read_buf( self, cx, buf )
+	}
+}
+
+
+impl TokioAsyncWrite for RingBuffer<u8>
+{
+	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, src: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write( self, cx, src )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_flush( self, cx )
+	}
+
+
+	/// Closes the buffer, like `AsyncWrite::poll_close` from the futures library.
+	//
+	fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_close( self, cx )
+	}
+}
+
+
+impl TokioAsyncRead for Endpoint
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		poll_read_buf( self, cx, buf )
+	}
+}
+
+
+impl TokioAsyncWrite for Endpoint
+{
+	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, src: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write( self, cx, src )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_flush( self, cx )
+	}
+
+
+	/// Closes the writing side of this endpoint, like `AsyncWrite::poll_close` from the futures library.
+	//
+	fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_close( self, cx )
+	}
+}
+
+
+
+impl TokioAsyncRead for ReadHalf<u8>
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		poll_read_buf( self, cx, buf )
+	}
+}
+
+
+impl TokioAsyncWrite for WriteHalf<u8>
+{
+	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, src: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write( self, cx, src )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_flush( self, cx )
+	}
+
+
+	fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_close( self, cx )
+	}
+}
+
+
+impl TokioAsyncRead for MockStream
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		poll_read_buf( self, cx, buf )
+	}
+}
+
+
+impl TokioAsyncWrite for MockStream
+{
+	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, src: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		AsyncWrite::poll_write( self, cx, src )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_flush( self, cx )
+	}
+
+
+	fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		AsyncWrite::poll_close( self, cx )
+	}
+}
+
+
+
+#[cfg(test)]
+//
+mod tests
+{
+	// What's tested:
+	//
+	// ✔ reading and writing with the tokio traits
+	// ✔ reading from a closed empty buffer signals end of stream
+	// ✔ writing after shutdown
+	//
+	use crate::{ import::{ *, assert_eq }, RingBuffer };
+
+
+	#[test]
+	//
+	fn read_write() { block_on( async
+	{
+		let mut ring = RingBuffer::<u8>::new(4);
+		let mut read = [0u8;4];
+
+		tokio::io::AsyncWriteExt::write_all( &mut ring, b"abc" ).await.expect( "write" );
+
+		assert_eq!( 3, tokio::io::AsyncReadExt::read( &mut ring, &mut read ).await.expect( "read" ) );
+		assert_eq!( &read[..3], b"abc" );
+	})}
+
+
+	#[test]
+	//
+	fn shutdown() { block_on( async
+	{
+		let mut ring = RingBuffer::<u8>::new(4);
+		let mut read = Vec::new();
+
+		tokio::io::AsyncWriteExt::write_all( &mut ring, b"abc" ).await.expect( "write" );
+		tokio::io::AsyncWriteExt::shutdown ( &mut ring          ).await.expect( "shutdown" );
+
+		tokio::io::AsyncReadExt::read_to_end( &mut ring, &mut read ).await.expect( "read" );
+		assert_eq!( read, b"abc" );
+
+		let err = tokio::io::AsyncWriteExt::write( &mut ring, b"d" ).await.unwrap_err();
+		assert_eq!( err.kind(), io::ErrorKind::NotConnected );
+	})}
+}
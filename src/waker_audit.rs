@@ -0,0 +1,417 @@
+use crate::import::*;
+
+
+/// A wrapper that checks that the wrapped type doesn't lose wake ups. Whenever it returns `Poll::Pending`,
+/// it must have stored the waker to wake up the task later, or woken it up already. Otherwise nobody will
+/// ever poll the task again and it hangs. This is the most common bug in custom implementations of
+/// `AsyncRead`/`AsyncWrite` and friends.
+///
+/// `WakerAudit` gives the wrapped type a waker of its own, which forwards to the waker of the task. When the
+/// wrapped type returns pending and nobody holds a clone of that waker and it hasn't been woken up, this
+/// panics. With [`WakerAudit::report_only`], it logs an error instead and counts the lost wake ups.
+///
+/// It implements `AsyncRead`, `AsyncBufRead`, `AsyncWrite`, `Stream`, `Sink` and `Future` when the wrapped type
+/// does, and with the `tokio` feature the tokio versions of `AsyncRead`/`AsyncWrite`. It can wrap a
+/// [`RingBuffer`](crate::RingBuffer), an [`Endpoint`](crate::Endpoint), a `Sketchy` or any type of your own.
+///
+/// ```
+/// use futures_ringbuf::{ Endpoint, WakerAudit };
+/// use futures::{ AsyncReadExt, AsyncWriteExt, executor::block_on, join };
+///
+/// let (server, client) = Endpoint::pair( 64, 64 );
+///
+/// let mut server = WakerAudit::new( server );
+/// let mut client = WakerAudit::new( client );
+///
+/// block_on( async
+/// {
+///    let mut buf = [0u8; 5];
+///
+///    // The read is pending until the write happens, which is fine because Endpoint stores the waker.
+///    //
+///    let (read, write) = join!( client.read( &mut buf ), server.write( b"Hello" ) );
+///
+///    assert_eq!( read.expect( "read" ), 5 );
+///    assert_eq!( write.expect( "write" ), 5 );
+/// });
+/// ```
+//
+#[ derive( Debug ) ]
+//
+pub struct WakerAudit<T>
+{
+	inner  : T       ,
+	auditor: Auditor ,
+}
+
+
+#[ derive( Debug ) ]
+//
+struct Auditor
+{
+	report_only: bool  ,
+	lost       : usize ,
+}
+
+
+/// The waker given to the wrapped type.
+//
+#[ derive( Debug ) ]
+//
+struct AuditWaker
+{
+	waker: Waker      ,
+	woken: AtomicBool ,
+}
+
+
+impl ArcWake for AuditWaker
+{
+	fn wake_by_ref( arc_self: &Arc<Self> )
+	{
+		arc_self.woken.store( true, Ordering::SeqCst );
+		arc_self.waker.wake_by_ref();
+	}
+}
+
+
+impl Auditor
+{
+	/// Create the waker for one call on the wrapped type.
+	//
+	fn start( cx: &Context<'_> ) -> (Waker, Arc<AuditWaker>)
+	{
+		let audit = Arc::new( AuditWaker{ waker: cx.waker().clone(), woken: AtomicBool::new( false ) } );
+
+		(futures::task::waker( audit.clone() ), audit)
+	}
+
+
+	/// Check whether a call that returned pending kept the waker.
+	//
+	fn finish( &mut self, what: &str, pending: bool, waker: Waker, audit: Arc<AuditWaker> )
+	{
+		drop( waker );
+
+		// Our own reference is the only one left, so nobody can wake up the task.
+		//
+		if !pending || Arc::strong_count( &audit ) > 1 || audit.woken.load( Ordering::SeqCst )
+		{
+			return;
+		}
+
+		self.lost += 1;
+
+		let msg = format!
+		(
+			"futures_ringbuf::WakerAudit: {} returned Poll::Pending without storing or waking up the waker, the task will hang",
+			what,
+		);
+
+		if self.report_only
+		{
+			log::error!( "{}", msg );
+		}
+
+		else
+		{
+			panic!( "{}", msg );
+		}
+	}
+}
+
+
+impl<T> WakerAudit<T>
+{
+	/// Wrap `inner`. Panics when a wake up is lost.
+	//
+	pub fn new( inner: T ) -> Self
+	{
+		Self
+		{
+			inner,
+			auditor: Auditor{ report_only: false, lost: 0 },
+		}
+	}
+
+
+	/// Log an error instead of panicking when a wake up is lost. Check [`WakerAudit::lost_wakeups`] at the
+	/// end of your test.
+	//
+	pub fn report_only( mut self ) -> Self
+	{
+		self.auditor.report_only = true;
+		self
+	}
+
+
+	/// The number of times the wrapped type returned pending without keeping the waker.
+	//
+	pub fn lost_wakeups( &self ) -> usize
+	{
+		self.auditor.lost
+	}
+
+
+	/// Access the wrapped type.
+	//
+	pub fn get_ref( &self ) -> &T
+	{
+		&self.inner
+	}
+
+
+	/// Access the wrapped type mutably.
+	//
+	pub fn get_mut( &mut self ) -> &mut T
+	{
+		&mut self.inner
+	}
+
+
+	/// Return the wrapped type.
+	//
+	pub fn into_inner( self ) -> T
+	{
+		self.inner
+	}
+}
+
+
+impl<T: Unpin> WakerAudit<T>
+{
+	/// Call `f` on the wrapped type with an audited waker. The result can borrow from the wrapped type,
+	/// as `poll_fill_buf` needs.
+	//
+	fn audit<'a, R>( self: Pin<&'a mut Self>, cx: &mut Context<'_>, what: &str, f: impl FnOnce( Pin<&'a mut T>, &mut Context<'_> ) -> Poll<R> ) -> Poll<R>
+	{
+		let Self{ inner, auditor } = self.get_mut();
+
+		let (waker, audit) = Auditor::start( cx );
+		let poll           = f( Pin::new( inner ), &mut Context::from_waker( &waker ) );
+
+		auditor.finish( what, poll.is_pending(), waker, audit );
+
+		poll
+	}
+}
+
+
+
+impl<T> AsyncRead for WakerAudit<T> where T: AsyncRead + Unpin
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		self.audit( cx, "AsyncRead::poll_read", |inner, cx| inner.poll_read( cx, buf ) )
+	}
+}
+
+
+
+impl<T> AsyncBufRead for WakerAudit<T> where T: AsyncBufRead + Unpin
+{
+	fn poll_fill_buf( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<&[u8]> >
+	{
+		self.audit( cx, "AsyncBufRead::poll_fill_buf", |inner, cx| inner.poll_fill_buf( cx ) )
+	}
+
+
+	fn consume( mut self: Pin<&mut Self>, amt: usize )
+	{
+		Pin::new( &mut self.inner ).consume( amt )
+	}
+}
+
+
+
+impl<T> AsyncWrite for WakerAudit<T> where T: AsyncWrite + Unpin
+{
+	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		self.audit( cx, "AsyncWrite::poll_write", |inner, cx| inner.poll_write( cx, buf ) )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.audit( cx, "AsyncWrite::poll_flush", |inner, cx| inner.poll_flush( cx ) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.audit( cx, "AsyncWrite::poll_close", |inner, cx| inner.poll_close( cx ) )
+	}
+}
+
+
+
+impl<T> Stream for WakerAudit<T> where T: Stream + Unpin
+{
+	type Item = T::Item;
+
+	fn poll_next( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Option<Self::Item> >
+	{
+		self.audit( cx, "Stream::poll_next", |inner, cx| inner.poll_next( cx ) )
+	}
+
+
+	fn size_hint( &self ) -> (usize, Option<usize>)
+	{
+		self.inner.size_hint()
+	}
+}
+
+
+
+impl<T, Item> Sink<Item> for WakerAudit<T> where T: Sink<Item> + Unpin
+{
+	type Error = T::Error;
+
+	fn poll_ready( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		self.audit( cx, "Sink::poll_ready", |inner, cx| inner.poll_ready( cx ) )
+	}
+
+
+	fn start_send( mut self: Pin<&mut Self>, item: Item ) -> Result<(), Self::Error>
+	{
+		Pin::new( &mut self.inner ).start_send( item )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		self.audit( cx, "Sink::poll_flush", |inner, cx| inner.poll_flush( cx ) )
+	}
+
+
+	fn poll_close( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Result<(), Self::Error> >
+	{
+		self.audit( cx, "Sink::poll_close", |inner, cx| inner.poll_close( cx ) )
+	}
+}
+
+
+
+impl<T> Future for WakerAudit<T> where T: Future + Unpin
+{
+	type Output = T::Output;
+
+	fn poll( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< Self::Output >
+	{
+		self.audit( cx, "Future::poll", |inner, cx| inner.poll( cx ) )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio" ) ]
+//
+impl<T> tokio::io::AsyncRead for WakerAudit<T> where T: tokio::io::AsyncRead + Unpin
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_> ) -> Poll< io::Result<()> >
+	{
+		self.audit( cx, "tokio::AsyncRead::poll_read", |inner, cx| inner.poll_read( cx, buf ) )
+	}
+}
+
+
+
+#[ cfg( feature = "tokio" ) ]
+//
+impl<T> tokio::io::AsyncWrite for WakerAudit<T> where T: tokio::io::AsyncWrite + Unpin
+{
+	fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll< io::Result<usize> >
+	{
+		self.audit( cx, "tokio::AsyncWrite::poll_write", |inner, cx| inner.poll_write( cx, buf ) )
+	}
+
+
+	fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.audit( cx, "tokio::AsyncWrite::poll_flush", |inner, cx| inner.poll_flush( cx ) )
+	}
+
+
+	fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll< io::Result<()> >
+	{
+		self.audit( cx, "tokio::AsyncWrite::poll_shutdown", |inner, cx| inner.poll_shutdown( cx ) )
+	}
+}
+
+
+
+/// Run `future` to completion on the current thread, like `futures::executor::block_on`, but panic when it
+/// deadlocks instead of hanging forever: when the future is pending, hasn't been woken up and nobody holds
+/// its waker anymore, nothing can ever wake it up again.
+///
+/// Run all the tasks of your test in this future, eg. with `join!`, so a deadlock between them is detected.
+/// Not available on WASM.
+///
+/// # Panics
+///
+/// When the future deadlocks.
+//
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+pub fn block_on_audited<F: Future>( future: F ) -> F::Output
+{
+	// Upper bound on how long we sleep, so we notice when the last clone of the waker is dropped.
+	//
+	const RECHECK: Duration = Duration::from_millis( 10 );
+
+	let task   = Arc::new( TaskWaker{ thread: std::thread::current(), woken: AtomicBool::new( false ) } );
+	let waker  = futures::task::waker( task.clone() );
+	let mut cx = Context::from_waker( &waker );
+
+	let mut future = Box::pin( future );
+
+	loop
+	{
+		task.woken.store( false, Ordering::SeqCst );
+
+		if let Poll::Ready( out ) = future.as_mut().poll( &mut cx )
+		{
+			return out;
+		}
+
+		// Wait for a wake up. `task` and `waker` are ours, any other reference can still wake us up.
+		//
+		while !task.woken.load( Ordering::SeqCst )
+		{
+			// Another thread might have woken us up and dropped its waker since we checked, so only
+			// trust `woken` when it's read after the count.
+			//
+			if Arc::strong_count( &task ) <= 2 && !task.woken.load( Ordering::SeqCst )
+			{
+				panic!( "futures_ringbuf::block_on_audited: deadlock, the future is pending and nothing holds its waker" );
+			}
+
+			std::thread::park_timeout( RECHECK );
+		}
+	}
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+#[ derive( Debug ) ]
+//
+struct TaskWaker
+{
+	thread: std::thread::Thread ,
+	woken : AtomicBool          ,
+}
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+impl ArcWake for TaskWaker
+{
+	fn wake_by_ref( arc_self: &Arc<Self> )
+	{
+		arc_self.woken.store( true, Ordering::SeqCst );
+		arc_self.thread.unpark();
+	}
+}
@@ -0,0 +1,131 @@
+#![ cfg( feature = "sketchy" ) ]
+
+// Tested:
+//
+// ✔ bit flips change exactly one bit per byte
+// ✔ substitution changes every byte
+// ✔ truncation loses the end of writes while the writer thinks all was written
+// ✔ only the configured direction is corrupted
+// ✔ with a lower probability only some bytes are corrupted, each by a single bit
+// ✔ the recorder sees what was written, not what was delivered
+//
+use
+{
+	futures_ringbuf :: { *                                               } ,
+	futures         :: { AsyncReadExt, AsyncWriteExt, executor::block_on } ,
+};
+
+
+/// Write `data` in chunks of 10 bytes from a to b with `corruption` and return what b receives.
+//
+fn run( data: &[u8], corruption: Corruption, seed: u64 ) -> Vec<u8>
+{
+	let (mut a, mut b) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( data.len() ).corrupt( corruption, seed ) )
+		.build()
+	;
+
+	block_on( async
+	{
+		for chunk in data.chunks( 10 )
+		{
+			a.write_all( chunk ).await.expect( "write" );
+		}
+
+		a.close().await.expect( "close" );
+
+		let mut received = Vec::new();
+		b.read_to_end( &mut received ).await.expect( "read" );
+
+		received
+	})
+}
+
+
+
+#[ test ]
+//
+fn bit_flip()
+{
+	let data     = vec![ 0u8; 100 ];
+	let received = run( &data, Corruption::new().bit_flip( 1.0 ), 1 );
+
+	assert_eq!( received.len(), 100 );
+	assert!( received.iter().all( |b| b.count_ones() == 1 ) );
+}
+
+
+
+#[ test ]
+//
+fn substitute()
+{
+	let data     = ( 0..100 ).collect::<Vec<u8>>();
+	let received = run( &data, Corruption::new().substitute( 1.0 ), 1 );
+
+	assert_eq!( received.len(), 100 );
+	assert!( received.iter().zip( &data ).all( |(r, d)| r != d ) );
+}
+
+
+
+#[ test ]
+//
+fn truncate()
+{
+	let data     = ( 0..100 ).collect::<Vec<u8>>();
+	let received = run( &data, Corruption::new().truncate( 1.0 ), 1 );
+
+	// Every chunk of 10 loses at least its last byte, what remains is in order.
+	//
+	assert!( received.len() <= 90 );
+	assert!( received.windows( 2 ).all( |w| w[0] < w[1] ) );
+	assert!( received.iter().all( |b| b % 10 != 9 ) );
+}
+
+
+
+#[ test ]
+//
+fn some_bytes()
+{
+	let data     = vec![ 0u8; 1000 ];
+	let received = run( &data, Corruption::new().bit_flip( 0.1 ), 1 );
+	let flipped  = received.iter().filter( |b| **b != 0 ).count();
+
+	assert_eq!( received.len(), 1000 );
+	assert!( received.iter().all( |b| b.count_ones() <= 1 ) );
+	assert!( flipped > 0 && flipped < 500, "flipped: {}", flipped );
+}
+
+
+
+#[ test ]
+//
+fn one_direction()
+{
+	let recorder = Recorder::new();
+
+	let (mut a, mut b) = EndpointBuilder::new()
+
+		.a_to_b  ( DirectionConfig::default().corrupt( Corruption::new().substitute( 1.0 ), 1 ) )
+		.recorder( recorder.clone() )
+		.build()
+	;
+
+	let mut buf = [0u8; 5];
+
+	block_on( async
+	{
+		a.write_all( b"hello" ).await.expect( "write" );
+		b.read_exact( &mut buf ).await.expect( "read" );
+		assert_ne!( &buf, b"hello" );
+
+		b.write_all( b"world" ).await.expect( "write" );
+		a.read_exact( &mut buf ).await.expect( "read" );
+		assert_eq!( &buf, b"world" );
+	});
+
+	assert_eq!( recorder.transcript().bytes_written_by( "a" ), b"hello" );
+}
@@ -0,0 +1,169 @@
+// Tested:
+//
+// ✔ message boundaries are preserved, including empty messages
+// ✔ both directions are independent
+// ✔ sending is pending when the queue is full and woken up when a message is received
+// ✔ messages larger than the maximum size are rejected
+// ✔ end of stream after close, once the queue is empty
+// ✔ broken pipe when the remote endpoint is dropped
+// ✔ Sink and Stream
+// ✔ use from different threads
+//
+use
+{
+	futures_ringbuf :: { *                                                         } ,
+	futures         :: { Sink, SinkExt, StreamExt, executor::block_on, future::join } ,
+	futures_test    :: { task::new_count_waker                                     } ,
+	assert_matches  :: { assert_matches                                            } ,
+	std             :: { io, task::{ Context, Poll }, pin::Pin                     } ,
+};
+
+
+
+#[ test ]
+//
+fn boundaries()
+{
+	let (mut a, mut b) = DatagramEndpoint::pair( 4, 10 );
+
+	block_on( async
+	{
+		a.send( b"hello"  ).await.expect( "send" );
+		a.send( b""       ).await.expect( "send" );
+		a.send( b"world!" ).await.expect( "send" );
+		b.send( b"back"   ).await.expect( "send" );
+
+		assert_eq!( b.recv().await.expect( "recv" ), b"hello"  );
+		assert_eq!( b.recv().await.expect( "recv" ), b""       );
+		assert_eq!( b.recv().await.expect( "recv" ), b"world!" );
+		assert_eq!( a.recv().await.expect( "recv" ), b"back"   );
+	});
+
+	assert_eq!( a.max_size(), 10 );
+}
+
+
+
+#[ test ]
+//
+fn queue_full()
+{
+	let (mut a, mut b) = DatagramEndpoint::pair( 2, 10 );
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+
+	block_on( a.send( b"1" ) ).expect( "send" );
+	block_on( a.send( b"2" ) ).expect( "send" );
+
+	assert!( Pin::new( &mut a ).poll_ready( &mut cx ).is_pending() );
+	assert_eq!( Pin::new( &mut a ).start_send( b"3".to_vec() ).expect_err( "full" ).kind(), io::ErrorKind::WouldBlock );
+
+	assert_eq!( block_on( b.recv() ).expect( "recv" ), b"1" );
+	assert_eq!( count.get(), 1 );
+
+	assert_matches!( Pin::new( &mut a ).poll_ready( &mut cx ), Poll::Ready( Ok(()) ) );
+	Pin::new( &mut a ).start_send( b"3".to_vec() ).expect( "send" );
+
+	assert_eq!( block_on( b.recv() ).expect( "recv" ), b"2" );
+	assert_eq!( block_on( b.recv() ).expect( "recv" ), b"3" );
+}
+
+
+
+#[ test ]
+//
+fn too_large()
+{
+	let (mut a, mut b) = DatagramEndpoint::pair( 2, 4 );
+
+	assert_eq!( block_on( a.send( b"hello" ) ).expect_err( "too large" ).kind(), io::ErrorKind::InvalidInput );
+	assert_eq!( block_on( a.feed( b"hello".to_vec() ) ).expect_err( "too large" ).kind(), io::ErrorKind::InvalidInput );
+
+	block_on( a.send( b"hell" ) ).expect( "send" );
+	assert_eq!( block_on( b.recv() ).expect( "recv" ), b"hell" );
+}
+
+
+
+#[ test ]
+//
+fn close()
+{
+	let (mut a, mut b) = DatagramEndpoint::pair( 4, 10 );
+
+	block_on( async
+	{
+		a.send( b"last" ).await.expect( "send" );
+		SinkExt::close( &mut a ).await.expect( "close" );
+
+		assert_eq!( a.send( b"more" ).await.expect_err( "closed" ).kind(), io::ErrorKind::NotConnected );
+
+		assert_eq!( b.recv().await.expect( "recv" ), b"last" );
+		assert_eq!( b.recv().await, None );
+
+		// The other direction still works.
+		//
+		b.send( b"reply" ).await.expect( "send" );
+		assert_eq!( a.recv().await.expect( "recv" ), b"reply" );
+
+		drop( a );
+
+		assert_eq!( b.send( b"gone" ).await.expect_err( "broken pipe" ).kind(), io::ErrorKind::BrokenPipe );
+		assert_eq!( b.recv().await, None );
+	});
+}
+
+
+
+#[ test ]
+//
+fn sink_stream()
+{
+	let (mut a, b) = DatagramEndpoint::pair( 2, 10 );
+
+	let send = async
+	{
+		let mut msgs = futures::stream::iter( vec![ Ok( b"a".to_vec() ), Ok( b"bc".to_vec() ), Ok( b"def".to_vec() ) ] );
+
+		a.send_all( &mut msgs ).await.expect( "send_all" );
+		SinkExt::close( &mut a ).await.expect( "close" );
+	};
+
+	let recv = b.collect::<Vec<_>>();
+
+	let ((), msgs) = block_on( join( send, recv ) );
+
+	assert_eq!( msgs, vec![ b"a".to_vec(), b"bc".to_vec(), b"def".to_vec() ] );
+}
+
+
+
+#[ test ]
+//
+fn threads()
+{
+	let (mut a, b) = DatagramEndpoint::pair( 3, 100 );
+
+	let sender = std::thread::spawn( move ||
+	{
+		block_on( async
+		{
+			for i in 0..1000u32
+			{
+				a.send( &vec![ i as u8; i as usize % 100 ] ).await.expect( "send" );
+			}
+		});
+	});
+
+	let msgs = block_on( b.collect::<Vec<_>>() );
+
+	sender.join().expect( "join" );
+
+	assert_eq!( msgs.len(), 1000 );
+
+	for (i, msg) in msgs.iter().enumerate()
+	{
+		assert_eq!( msg, &vec![ i as u8; i % 100 ] );
+	}
+}
@@ -0,0 +1,111 @@
+#![ cfg( feature = "sketchy" ) ]
+
+// Tested:
+//
+// ✔ drop all messages
+// ✔ duplicate all messages
+// ✔ reorder swaps messages and close delivers the held back one
+// ✔ dropping the endpoint delivers the held back message
+// ✔ with a lower probability only some messages are dropped or duplicated, in order
+//
+use
+{
+	futures_ringbuf :: { *                                      } ,
+	futures         :: { SinkExt, StreamExt, executor::block_on } ,
+};
+
+
+/// Send the numbers in `0..n` as messages with `faults` and return what was received.
+//
+fn run( n: u8, faults: DatagramFaults, seed: u64 ) -> Vec<u8>
+{
+	let (a, b) = DatagramEndpoint::pair( 2 * n as usize + 1, 1 );
+	let mut a  = a.with_faults( faults, seed );
+
+	block_on( async
+	{
+		for i in 0..n
+		{
+			a.send( &[i] ).await.expect( "send" );
+		}
+
+		SinkExt::close( &mut a ).await.expect( "close" );
+
+		b.map( |msg| msg[0] ).collect().await
+	})
+}
+
+
+
+#[ test ]
+//
+fn drop_all()
+{
+	assert_eq!( run( 5, DatagramFaults::new().loss( 1.0 ), 1 ), Vec::<u8>::new() );
+}
+
+
+
+#[ test ]
+//
+fn duplicate()
+{
+	assert_eq!( run( 3, DatagramFaults::new().duplicate( 1.0 ), 1 ), vec![ 0, 0, 1, 1, 2, 2 ] );
+}
+
+
+
+#[ test ]
+//
+fn reorder()
+{
+	assert_eq!( run( 5, DatagramFaults::new().reorder( 1.0 ), 1 ), vec![ 1, 0, 3, 2, 4 ] );
+}
+
+
+
+#[ test ]
+//
+fn reorder_drop()
+{
+	let (a, mut b) = DatagramEndpoint::pair( 4, 1 );
+	let mut a      = a.with_faults( DatagramFaults::new().reorder( 1.0 ), 1 );
+
+	block_on( async
+	{
+		a.send( &[0] ).await.expect( "send" );
+		drop( a );
+
+		assert_eq!( b.recv().await, Some( vec![ 0 ] ) );
+		assert_eq!( b.recv().await, None              );
+	})
+}
+
+
+
+#[ test ]
+//
+fn some_lost()
+{
+	let received = run( 100, DatagramFaults::new().loss( 0.5 ), 1 );
+
+	assert!( !received.is_empty() && received.len() < 100, "received: {:?}", received );
+	assert!( received.windows( 2 ).all( |w| w[0] < w[1] ), "received: {:?}", received );
+}
+
+
+
+#[ test ]
+//
+fn some_duplicated()
+{
+	let mut received = run( 50, DatagramFaults::new().duplicate( 0.5 ), 1 );
+	let     len      = received.len();
+
+	assert!( received.windows( 2 ).all( |w| w[0] <= w[1] ), "received: {:?}", received );
+
+	received.dedup();
+
+	assert!( len > 50 && len < 100, "received {} messages", len );
+	assert_eq!( received, ( 0..50 ).collect::<Vec<u8>>() );
+}
@@ -5,17 +5,27 @@
 // ✔ try to write after close
 // ✔ read remaining data after close
 // ✔ wake up pending reader after call to close
+// ✔ read_line/read_until through AsyncBufRead
+// ✔ into_split and use the halves from different threads
 // - wake up pending reader after drop (requires an async drop)
+// ✔ writing after the remote endpoint is dropped returns BrokenPipe
+// ✔ wake up pending writer when the remote endpoint is dropped
+// ✔ shutdown read, write and both
+// ✔ reset fails all operations on both endpoints and wakes pending tasks
+// ✔ reset from a handle
+// ✔ configure each direction with the builder
+// ✔ local and peer addresses
 //
 use
 {
 	futures_ringbuf    :: { *                                                                      } ,
 	asynchronous_codec :: { Framed, LinesCodec                                                     } ,
 	futures            :: { AsyncRead, AsyncWrite, AsyncWriteExt, AsyncReadExt, executor::block_on } ,
+	futures            :: { AsyncBufReadExt                                                        } ,
 	futures            :: { future::join, SinkExt, StreamExt, channel::oneshot                     } ,
 	futures_test       :: { task::noop_waker                                                       } ,
 	assert_matches     :: { assert_matches                                                         } ,
-	std                :: { task::{ Poll, Context }                                                } ,
+	std                :: { task::{ Poll, Context }, pin::Pin                                      } ,
 	ergo_pin           :: { ergo_pin                                                               } ,
 };
 
@@ -38,6 +48,67 @@ fn basic_usage() { block_on( async
 })}
 
 
+#[ test ]
+//
+fn buf_read() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 32, 10 );
+
+	let mut line = String::new();
+	let mut word = Vec::new();
+
+	server.write_all( b"Hello World\nsecond;third" ).await.expect( "write" );
+	server.close().await.expect( "close" );
+
+	client.read_line( &mut line ).await.expect( "read_line" );
+	assert_eq!( line, "Hello World\n" );
+
+	client.read_until( b';', &mut word ).await.expect( "read_until" );
+	assert_eq!( word, b"second;" );
+
+	// What remains can still be read with AsyncRead.
+	//
+	let mut rest = Vec::new();
+	client.read_to_end( &mut rest ).await.expect( "read_to_end" );
+	assert_eq!( rest, b"third" );
+})}
+
+
+#[ test ]
+//
+fn into_split()
+{
+	let (server, client) = Endpoint::pair( 10, 10 );
+
+	let (mut srv_reader, mut srv_writer) = server.into_split();
+
+	let echo = std::thread::spawn( move || block_on( async move
+	{
+		futures::io::copy( &mut srv_reader, &mut srv_writer ).await.expect( "echo" );
+	}));
+
+	block_on( async move
+	{
+		let (mut cl_reader, mut cl_writer) = client.into_split();
+
+		let data: Vec<u8> = (0..100).collect();
+		let mut read      = Vec::new();
+
+		let send = async
+		{
+			cl_writer.write_all( &data ).await.expect( "write" );
+			AsyncWriteExt::close( &mut cl_writer ).await.expect( "close" );
+		};
+
+		join( send, cl_reader.read_to_end( &mut read ) ).await.1.expect( "read" );
+
+		assert_eq!( read, data );
+	});
+
+	echo.join().expect( "join echo thread" );
+}
+
+
 #[ test ] #[ ergo_pin ]
 //
 fn close_write()
@@ -180,3 +251,258 @@ fn drop_wake_pending()
 	//
 	block_on( join( clt, svr ) );
 }
+
+
+
+#[ test ]
+//
+fn drop_reader_broken_pipe() { block_on( async
+{
+	let (mut server, client) = Endpoint::pair( 10, 10 );
+
+	server.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+
+	drop( client );
+
+	let res = server.write( &[ 4,5,6 ] ).await;
+
+	assert_eq!( res.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe );
+})}
+
+
+
+#[ test ]
+//
+fn drop_reader_wake_pending()
+{
+	let (mut server, client) = Endpoint::pair( 3, 10 );
+	let (sender, receiver)   = oneshot::channel::<()>();
+
+	let svr = async move
+	{
+		// The buffer only holds 3 bytes, so this will be pending until the client is dropped.
+		//
+		sender.send(()).expect( "write channel" );
+
+		let res = server.write_all( &[ 1,2,3,4,5,6 ] ).await;
+
+		assert_eq!( res.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe );
+	};
+
+	let clt = async move
+	{
+		receiver.await.expect( "read channel" );
+
+		drop( client );
+	};
+
+	block_on( join( svr, clt ) );
+}
+
+
+
+#[ test ]
+//
+fn shutdown_read() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 10, 10 );
+
+	let mut read = [0u8;3];
+
+	server.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+
+	client.shutdown_direction( Shutdown::Read ).expect( "shutdown" );
+
+	// Buffered data is discarded.
+	//
+	assert_eq!( client.read( &mut read ).await.expect( "read" ), 0 );
+	assert_eq!( server.write( &[ 4 ] ).await.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe );
+
+	// The other direction still works.
+	//
+	client.write_all( &[ 5,6,7 ] ).await.expect( "write" );
+	server.read_exact( &mut read ).await.expect( "read" );
+
+	assert_eq!( read, [ 5,6,7 ] );
+})}
+
+
+
+#[ test ]
+//
+fn shutdown_write() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 10, 10 );
+
+	let mut read = Vec::new();
+
+	server.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+	server.shutdown_direction( Shutdown::Write ).expect( "shutdown" );
+
+	assert_eq!( server.write( &[ 4 ] ).await.unwrap_err().kind(), std::io::ErrorKind::NotConnected );
+
+	client.read_to_end( &mut read ).await.expect( "read" );
+	assert_eq!( read, vec![ 1,2,3 ] );
+
+	// The other direction still works.
+	//
+	client.write_all( &[ 5,6,7 ] ).await.expect( "write" );
+	client.close().await.expect( "close" );
+
+	read.clear();
+	server.read_to_end( &mut read ).await.expect( "read" );
+
+	assert_eq!( read, vec![ 5,6,7 ] );
+})}
+
+
+
+#[ test ]
+//
+fn shutdown_both() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 10, 10 );
+
+	let mut read = [0u8;3];
+
+	client.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+	server.shutdown_direction( Shutdown::Both ).expect( "shutdown" );
+
+	assert_eq!( server.read ( &mut read ).await.expect( "read" ), 0 );
+	assert_eq!( client.read ( &mut read ).await.expect( "read" ), 0 );
+
+	assert_eq!( server.write( &[ 4 ] ).await.unwrap_err().kind(), std::io::ErrorKind::NotConnected );
+	assert_eq!( client.write( &[ 4 ] ).await.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe   );
+})}
+
+
+
+#[ test ]
+//
+fn reset() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 10, 10 );
+
+	let mut read = [0u8;3];
+
+	server.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+	client.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+
+	server.reset();
+
+	for endpoint in [ &mut server, &mut client ]
+	{
+		assert_eq!( endpoint.read ( &mut read ).await.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+		assert_eq!( endpoint.write( &[ 4 ]    ).await.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+		assert_eq!( endpoint.flush(           ).await.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+		assert_eq!( endpoint.close(           ).await.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+
+		assert_eq!( endpoint.shutdown_direction( Shutdown::Both ).unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+
+		// Should be the same
+		//
+		assert_eq!( endpoint.read ( &mut read ).await.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+	}
+})}
+
+
+
+#[ test ]
+//
+fn reset_handle_wake_pending()
+{
+	let (server, client)   = Endpoint::pair( 3, 10 );
+	let (sender, receiver) = oneshot::channel::<()>();
+
+	let handle = client.reset_handle();
+	let (mut reader, mut writer) = server.into_split();
+
+	let write = async move
+	{
+		sender.send(()).expect( "write channel" );
+
+		// The buffer only holds 3 bytes and the client doesn't read, so this will be pending until the reset.
+		//
+		let res = writer.write_all( &[ 1,2,3,4,5,6 ] ).await;
+
+		assert_eq!( res.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+	};
+
+	let read = async move
+	{
+		let mut buf = [0u8;1];
+
+		// The client doesn't write, so this will be pending until the reset.
+		//
+		let res = reader.read( &mut buf ).await;
+
+		assert_eq!( res.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset );
+	};
+
+	let reset = async move
+	{
+		receiver.await.expect( "read channel" );
+
+		handle.reset();
+	};
+
+	block_on( async { futures::join!( write, read, reset ) } );
+
+	drop( client );
+}
+
+
+
+#[ test ]
+//
+fn builder()
+{
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_name( "server" )
+		.b_name( "client" )
+		.a_to_b( DirectionConfig::new( 2 ) )
+		.b_to_a( DirectionConfig::new( 4 ) )
+		.build()
+	;
+
+	assert_eq!( server.name(), "server" );
+	assert_eq!( client.name(), "client" );
+
+	let     waker = noop_waker();
+	let mut cx    = Context::from_waker( &waker );
+
+	// The server can write 2 bytes and the client 4.
+	//
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &[ 1,2,3,4,5 ] ), Poll::Ready( Ok(2) ) );
+	assert_matches!( Pin::new( &mut client ).poll_write( &mut cx, &[ 1,2,3,4,5 ] ), Poll::Ready( Ok(4) ) );
+}
+
+
+
+#[ test ]
+//
+fn addresses()
+{
+	let (a, b) = Endpoint::pair( 10, 10 );
+
+	assert_eq!( a.local_addr().expect( "addr" ), "127.0.0.1:49152".parse().expect( "parse" ) );
+	assert_eq!( a.peer_addr ().expect( "addr" ), "127.0.0.1:49153".parse().expect( "parse" ) );
+	assert_eq!( b.local_addr().expect( "addr" ), a.peer_addr ().expect( "addr" )            );
+	assert_eq!( b.peer_addr ().expect( "addr" ), a.local_addr().expect( "addr" )            );
+
+	let server_addr = "[::1]:443"     .parse().expect( "parse" );
+	let client_addr = "10.0.0.7:50000".parse().expect( "parse" );
+
+	let (server, client) = EndpointBuilder::new()
+
+		.a_addr( server_addr )
+		.b_addr( client_addr )
+		.build()
+	;
+
+	assert_eq!( server.local_addr().expect( "addr" ), server_addr );
+	assert_eq!( server.peer_addr ().expect( "addr" ), client_addr );
+	assert_eq!( client.local_addr().expect( "addr" ), client_addr );
+	assert_eq!( client.peer_addr ().expect( "addr" ), server_addr );
+}
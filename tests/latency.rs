@@ -0,0 +1,152 @@
+// Tested:
+//
+// ✔ data only becomes readable after the latency
+// ✔ pending reader woken up when data matures
+// ✔ polling again doesn't register another timer for the same data
+// ✔ end of stream only after the data in transit has been read
+// ✔ the other direction is not affected
+// ✔ latency with the default system clock
+//
+use
+{
+	futures_ringbuf :: { *                                                                   } ,
+	futures         :: { AsyncRead, AsyncReadExt, AsyncWriteExt, executor::block_on          } ,
+	futures_test    :: { task::new_count_waker                                               } ,
+	assert_matches  :: { assert_matches                                                      } ,
+	std             :: { task::{ Context, Poll }, pin::Pin, time::{ Duration, Instant }      } ,
+};
+
+
+
+#[ test ]
+//
+fn latency()
+{
+	let clock = VirtualClock::new();
+
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( 10 ).latency( Duration::from_millis( 50 ) ) )
+		.clock ( clock.clone() )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let mut buf        = [0u8; 10];
+
+	block_on( server.write_all( &[ 1,2,3 ] ) ).expect( "write" );
+
+	assert!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+	clock.advance( Duration::from_millis( 30 ) );
+
+	block_on( server.write_all( &[ 4,5 ] ) ).expect( "write" );
+
+	// The writer wakes up the reader so it can ask the clock to be woken when the data matures.
+	//
+	assert!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+	let woken = count.get();
+	assert!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+
+	// Only the first write has matured.
+	//
+	clock.advance( Duration::from_millis( 20 ) );
+
+	assert!( count.get() > woken );
+	assert_matches!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(3) ) );
+	assert_eq!( &buf[..3], &[ 1,2,3 ] );
+
+	assert!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+
+	// Closing doesn't let the reader skip the data in transit.
+	//
+	block_on( server.close() ).expect( "close" );
+	assert!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+	clock.advance( Duration::from_millis( 30 ) );
+
+	assert_matches!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(2) ) );
+	assert_eq!( &buf[..2], &[ 4,5 ] );
+
+	assert_matches!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(0) ) );
+
+
+	// The other direction has no latency.
+	//
+	block_on( client.write_all( &[ 6 ] ) ).expect( "write" );
+	assert_matches!( Pin::new( &mut server ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(1) ) );
+}
+
+
+
+#[ test ]
+//
+fn one_timer()
+{
+	let clock = VirtualClock::new();
+
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( 10 ).latency( Duration::from_millis( 50 ) ) )
+		.clock ( clock.clone() )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let mut buf        = [0u8; 10];
+
+	block_on( server.write_all( &[ 1,2,3 ] ) ).expect( "write" );
+
+	for _ in 0..5
+	{
+		assert!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ).is_pending() );
+	}
+
+	assert_eq!( clock.pending_timers(), 1 );
+
+	// A different task polling needs a timer of its own.
+	//
+	let (other, _) = new_count_waker();
+
+	assert!( Pin::new( &mut client ).poll_read( &mut Context::from_waker( &other ), &mut buf ).is_pending() );
+	assert_eq!( clock.pending_timers(), 2 );
+
+	clock.advance( Duration::from_millis( 50 ) );
+
+	assert_eq!( count.get(), 1 );
+	assert_matches!( Pin::new( &mut client ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(3) ) );
+}
+
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+#[ test ]
+//
+fn system_clock()
+{
+	let latency = Duration::from_millis( 20 );
+
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::default().latency( latency ) )
+		.build()
+	;
+
+	let mut buf = [0u8; 3];
+	let start   = Instant::now();
+
+	block_on( async
+	{
+		server.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+		client.read_exact( &mut buf ).await.expect( "read" );
+	});
+
+	assert!( start.elapsed() >= latency );
+	assert_eq!( buf, [ 1,2,3 ] );
+}
@@ -0,0 +1,230 @@
+// Tested:
+//
+// ✔ reads and writes following the script
+// ✔ reads wait for the writes before them
+// ✔ reads in small chunks and writes in several calls
+// ✔ end of stream after the script
+// ✔ read and write errors
+// ✔ wait blocks reading and writing until it elapsed
+// ✔ polling a wait again doesn't add timers
+// ✔ panic with the offset and the bytes on unexpected data
+// ✔ panic on a write when none is expected
+// ✔ panic when dropped before the script is done
+//
+use
+{
+	futures_ringbuf :: { *                                                                   } ,
+	futures         :: { AsyncRead, AsyncWrite, AsyncReadExt, AsyncWriteExt, executor::block_on } ,
+	futures_test    :: { task::new_count_waker                                               } ,
+	assert_matches  :: { assert_matches                                                      } ,
+	std             :: { task::{ Context, Poll }, pin::Pin, time::Duration, io               } ,
+};
+
+
+
+#[ test ]
+//
+fn script()
+{
+	let mut mock = MockStreamBuilder::new()
+
+		.read ( b"hello" )
+		.write( b"world" )
+		.read ( b"!"     )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let mut buf        = [0u8; 3];
+
+	// Reads in small chunks.
+	//
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(3) ) );
+	assert_eq!( &buf, b"hel" );
+
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(2) ) );
+	assert_eq!( &buf[..2], b"lo" );
+
+	// The next read waits for the write.
+	//
+	assert!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+	assert_matches!( Pin::new( &mut mock ).poll_write( &mut cx, b"wor" ), Poll::Ready( Ok(3) ) );
+	assert_eq!( count.get(), 0 );
+
+	assert_matches!( Pin::new( &mut mock ).poll_write( &mut cx, b"ld" ), Poll::Ready( Ok(2) ) );
+	assert_eq!( count.get(), 1 );
+
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(1) ) );
+	assert_eq!( &buf[..1], b"!" );
+
+	assert!( mock.is_done() );
+
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(0) ) );
+}
+
+
+
+#[ test ]
+//
+fn write_skips_reads()
+{
+	let mut mock = MockStreamBuilder::new()
+
+		.read ( b"ping" )
+		.write( b"pong" )
+		.build()
+	;
+
+	block_on( async
+	{
+		mock.write_all( b"pong" ).await.expect( "write" );
+
+		let mut buf = vec![];
+		mock.read_to_end( &mut buf ).await.expect( "read" );
+
+		assert_eq!( buf, b"ping" );
+	});
+}
+
+
+
+#[ test ]
+//
+fn errors()
+{
+	let mut mock = MockStreamBuilder::new()
+
+		.read_error ( io::ErrorKind::ConnectionReset )
+		.write_error( io::ErrorKind::BrokenPipe      )
+		.build()
+	;
+
+	block_on( async
+	{
+		let mut buf = [0u8; 4];
+
+		assert_eq!( mock.write( b"data" ).await.expect_err( "write error" ).kind(), io::ErrorKind::BrokenPipe      );
+		assert_eq!( mock.read( &mut buf ).await.expect_err( "read error"  ).kind(), io::ErrorKind::ConnectionReset );
+	});
+
+	assert!( mock.is_done() );
+}
+
+
+
+#[ test ]
+//
+fn wait()
+{
+	let clock = VirtualClock::new();
+
+	let mut mock = MockStreamBuilder::new()
+
+		.read ( b"a" )
+		.wait ( Duration::from_millis( 10 ) )
+		.read ( b"b" )
+		.write( b"c" )
+		.clock( clock.clone() )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let mut buf        = [0u8; 4];
+
+	// The write is behind the wait, which only starts after the first read.
+	//
+	assert!( Pin::new( &mut mock ).poll_write( &mut cx, b"c" ).is_pending() );
+
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(1) ) );
+	assert_eq!( count.get(), 1 );
+
+	assert!( Pin::new( &mut mock ).poll_read ( &mut cx, &mut buf ).is_pending() );
+	assert!( Pin::new( &mut mock ).poll_write( &mut cx, b"c"     ).is_pending() );
+
+	clock.advance( Duration::from_millis( 9 ) );
+	assert!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+	let woken = count.get();
+	clock.advance( Duration::from_millis( 1 ) );
+	assert!( count.get() > woken );
+
+	assert_matches!( Pin::new( &mut mock ).poll_write( &mut cx, b"c" ), Poll::Ready( Ok(1) ) );
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(1) ) );
+	assert_eq!( &buf[..1], b"b" );
+}
+
+
+
+#[ test ]
+//
+fn wait_one_timer()
+{
+	let clock = VirtualClock::new();
+
+	let mut mock = MockStreamBuilder::new()
+
+		.wait ( Duration::from_millis( 10 ) )
+		.read ( b"a" )
+		.clock( clock.clone() )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let mut buf        = [0u8; 4];
+
+	for _ in 0..5
+	{
+		assert!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ).is_pending() );
+	}
+
+	assert_eq!( clock.pending_timers(), 1 );
+
+	clock.advance( Duration::from_millis( 10 ) );
+
+	assert_eq!( count.get(), 1 );
+	assert_matches!( Pin::new( &mut mock ).poll_read( &mut cx, &mut buf ), Poll::Ready( Ok(1) ) );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "unexpected bytes written at offset 9.\nexpected: b\"ld\"\n     got: b\"\\x00d\"" ) ]
+//
+fn wrong_bytes()
+{
+	let mut mock = MockStreamBuilder::new().write( b"hello world" ).build();
+
+	block_on( mock.write_all( b"hello wor\0d" ) ).expect( "write" );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "unexpected write at offset 5" ) ]
+//
+fn unexpected_write()
+{
+	let mut mock = MockStreamBuilder::new().write( b"hello" ).build();
+
+	block_on( mock.write_all( b"hello world" ) ).expect( "write" );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "dropped before the script was done" ) ]
+//
+fn unfinished()
+{
+	let mut mock = MockStreamBuilder::new().read( b"hello" ).write( b"world" ).build();
+
+	let mut buf = [0u8; 5];
+	block_on( mock.read_exact( &mut buf ) ).expect( "read" );
+}
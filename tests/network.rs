@@ -0,0 +1,201 @@
+// Tested:
+//
+// ✔ accept connections in a loop
+// ✔ connection refused on an unbound address
+// ✔ address in use
+// ✔ dropping the listener frees the address and drops pending connections
+// ✔ pending accept woken up by connect
+// ✔ connections use the builder of the network
+// ✔ connections share the default clock
+// ✔ addresses of the connections and the listener
+//
+use
+{
+	futures_ringbuf :: { *                                                                       } ,
+	futures         :: { AsyncReadExt, AsyncWriteExt, StreamExt, executor::block_on, future::join } ,
+	futures_test    :: { task::new_count_waker                                                   } ,
+	std             :: { io, task::Context                                                       } ,
+};
+
+
+
+#[ test ]
+//
+fn accept_loop()
+{
+	let network  = MockNetwork::new();
+	let listener = network.bind( "server:1234" ).expect( "bind" );
+
+	assert_eq!( listener.addr()                       , "server:1234"                             );
+	assert_eq!( listener.local_addr().expect( "addr" ), "127.0.0.1:49153".parse().expect( "parse" ) );
+
+	// An echo server that handles 3 connections.
+	//
+	let server = listener.take( 3 ).for_each( |conn| async
+	{
+		let mut conn = conn.expect( "accept" );
+		let mut buf  = [0u8; 5];
+
+		conn.read_exact( &mut buf ).await.expect( "read" );
+		conn.write_all( &buf ).await.expect( "write" );
+	});
+
+	let clients = async
+	{
+		for i in 1..=3
+		{
+			let mut client = network.connect( "server:1234" ).expect( "connect" );
+			let mut buf    = [0u8; 5];
+
+			assert_eq!( client.name(), format!( "client-{}", i ) );
+
+			client.write_all( b"hello" ).await.expect( "write" );
+			client.read_exact( &mut buf ).await.expect( "read" );
+
+			assert_eq!( &buf, b"hello" );
+		}
+	};
+
+	block_on( join( server, clients ) );
+}
+
+
+
+#[ test ]
+//
+fn refused()
+{
+	let network = MockNetwork::new();
+
+	assert_eq!( network.connect( "server:1234" ).expect_err( "refused" ).kind(), io::ErrorKind::ConnectionRefused );
+
+	let _listener = network.bind( "server:1234" ).expect( "bind" );
+
+	assert_eq!( network.bind( "server:1234" ).expect_err( "in use" ).kind(), io::ErrorKind::AddrInUse );
+	assert_eq!( network.connect( "server:4321" ).expect_err( "refused" ).kind(), io::ErrorKind::ConnectionRefused );
+}
+
+
+
+#[ test ]
+//
+fn drop_listener()
+{
+	let network  = MockNetwork::new();
+	let listener = network.bind( "server:1234" ).expect( "bind" );
+
+	let mut pending = network.connect( "server:1234" ).expect( "connect" );
+
+	drop( listener );
+
+	assert_eq!( network.connect( "server:1234" ).expect_err( "refused" ).kind(), io::ErrorKind::ConnectionRefused );
+
+	let mut buf = [0u8; 1];
+	assert_eq!( block_on( pending.read( &mut buf ) ).expect( "end of stream" ), 0 );
+
+	// The address can be bound again.
+	//
+	let _listener = network.bind( "server:1234" ).expect( "bind" );
+	network.connect( "server:1234" ).expect( "connect" );
+}
+
+
+
+#[ test ]
+//
+fn wake_accept()
+{
+	let network      = MockNetwork::new();
+	let mut listener = network.bind( "server:1234" ).expect( "bind" );
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+
+	assert!( listener.poll_next_unpin( &mut cx ).is_pending() );
+
+	let _client = network.connect( "server:1234" ).expect( "connect" );
+
+	assert_eq!( count.get(), 1 );
+
+	let server = block_on( listener.accept() ).expect( "accept" );
+
+	assert_eq!( server.name(), "server:1234" );
+}
+
+
+
+#[ test ]
+//
+fn with_builder()
+{
+	let recorder = Recorder::new();
+	let network  = MockNetwork::with_builder( EndpointBuilder::new().recorder( recorder.clone() ) );
+
+	let mut listener = network.bind( "server:1234" ).expect( "bind" );
+	let mut client   = network.connect( "server:1234" ).expect( "connect" );
+
+	block_on( client.write_all( b"hello" ) ).expect( "write" );
+	drop( block_on( listener.accept() ) );
+
+	assert_eq!( recorder.transcript().records()[0].from(), "client-1"    );
+	assert_eq!( recorder.transcript().records()[0].to()  , "server:1234" );
+}
+
+
+
+#[ cfg( not( target_arch = "wasm32" ) ) ]
+//
+#[ test ]
+//
+fn shared_clock()
+{
+	let recorder = Recorder::new();
+	let builder  = EndpointBuilder::new()
+
+		.a_to_b  ( DirectionConfig::default().latency( std::time::Duration::from_millis( 1 ) ) )
+		.recorder( recorder.clone() )
+	;
+
+	let network      = MockNetwork::with_builder( builder );
+	let mut listener = network.bind( "server:1234" ).expect( "bind" );
+
+	let _first = network.connect( "server:1234" ).expect( "connect" );
+
+	std::thread::sleep( std::time::Duration::from_millis( 50 ) );
+
+	let mut second = network.connect( "server:1234" ).expect( "connect" );
+
+	block_on( second.write_all( b"hello" ) ).expect( "write" );
+	drop( block_on( listener.accept() ) );
+
+	// A clock of its own would have started when the second connection was made.
+	//
+	let timestamp = recorder.transcript().records()[0].timestamp().expect( "timestamp" );
+
+	assert!( timestamp >= std::time::Duration::from_millis( 50 ), "timestamp: {:?}", timestamp );
+}
+
+
+
+#[ test ]
+//
+fn addresses()
+{
+	let network      = MockNetwork::new();
+	let mut listener = network.bind( "10.0.0.1:80" ).expect( "bind" );
+
+	let client1 = network.connect( "10.0.0.1:80" ).expect( "connect" );
+	let client2 = network.connect( "10.0.0.1:80" ).expect( "connect" );
+
+	let server1 = block_on( listener.accept() ).expect( "accept" );
+	let server2 = block_on( listener.accept() ).expect( "accept" );
+
+	assert_eq!( client1.peer_addr ().expect( "addr" ), "10.0.0.1:80"    .parse().expect( "parse" ) );
+	assert_eq!( client1.local_addr().expect( "addr" ), "127.0.0.1:49152".parse().expect( "parse" ) );
+	assert_eq!( client2.local_addr().expect( "addr" ), "127.0.0.1:49153".parse().expect( "parse" ) );
+
+	assert_eq!( server1.local_addr().expect( "addr" ), client1.peer_addr ().expect( "addr" ) );
+	assert_eq!( server1.local_addr().expect( "addr" ), listener.local_addr().expect( "addr" ) );
+	assert_eq!( server1.peer_addr ().expect( "addr" ), client1.local_addr().expect( "addr" ) );
+	assert_eq!( server2.peer_addr ().expect( "addr" ), client2.local_addr().expect( "addr" ) );
+}
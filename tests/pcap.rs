@@ -0,0 +1,196 @@
+// Tested:
+//
+// ✔ pcap header
+// ✔ handshake followed by a packet per write
+// ✔ addresses, ports, sequence and acknowledgement numbers
+// ✔ ip and tcp checksums
+// ✔ timestamps from the clock
+// ✔ ephemeral ports are reused when there are more clients than ports
+//
+use
+{
+	futures_ringbuf :: { *                                               } ,
+	futures         :: { AsyncReadExt, AsyncWriteExt, executor::block_on } ,
+	std             :: { time::Duration                                  } ,
+};
+
+
+// A packet parsed back from the pcap file.
+//
+#[ derive( Debug ) ]
+//
+struct Packet
+{
+	time : Duration ,
+	src  : [u8; 4]  ,
+	dst  : [u8; 4]  ,
+	sport: u16      ,
+	dport: u16      ,
+	seq  : u32      ,
+	ack  : u32      ,
+	flags: u8       ,
+	data : Vec<u8>  ,
+}
+
+
+fn u16_be( b: &[u8] ) -> u16 { u16::from_be_bytes( [ b[0], b[1] ] ) }
+fn u32_be( b: &[u8] ) -> u32 { u32::from_be_bytes( [ b[0], b[1], b[2], b[3] ] ) }
+fn u32_le( b: &[u8] ) -> u32 { u32::from_le_bytes( [ b[0], b[1], b[2], b[3] ] ) }
+
+
+// The internet checksum over data that includes its checksum field is zero.
+//
+fn verify( data: &[u8] ) -> bool
+{
+	let mut sum: u32 = data.chunks( 2 ).map( |w| u32::from( w[0] ) << 8 | u32::from( *w.get( 1 ).unwrap_or( &0 ) ) ).sum();
+
+	while sum > 0xffff { sum = ( sum & 0xffff ) + ( sum >> 16 ) }
+
+	sum == 0xffff
+}
+
+
+fn parse( pcap: &[u8] ) -> Vec<Packet>
+{
+	assert_eq!( u32_le( &pcap[ 0..] ), 0xa1b2_3c4d );
+	assert_eq!( u32_le( &pcap[20..] ), 101         );
+
+	let mut packets = Vec::new();
+	let mut rest    = &pcap[24..];
+
+	while !rest.is_empty()
+	{
+		let time = Duration::new( u32_le( rest ) as u64, u32_le( &rest[4..] ) );
+		let len  = u32_le( &rest[8..] ) as usize;
+		let ip   = &rest[ 16..16 + len ];
+		let tcp  = &ip[20..];
+
+		assert_eq!( u16_be( &ip[2..] ) as usize, len );
+		assert!( verify( &ip[..20] ), "ip checksum" );
+
+		let mut pseudo = Vec::new();
+		pseudo.extend_from_slice( &ip[12..20] );
+		pseudo.extend_from_slice( &[ 0, 6 ] );
+		pseudo.extend_from_slice( &( tcp.len() as u16 ).to_be_bytes() );
+		pseudo.extend_from_slice( tcp );
+
+		assert!( verify( &pseudo ), "tcp checksum" );
+
+		packets.push( Packet
+		{
+			time                                       ,
+			src  : [ ip[12], ip[13], ip[14], ip[15] ]  ,
+			dst  : [ ip[16], ip[17], ip[18], ip[19] ]  ,
+			sport: u16_be( &tcp[0..] )                 ,
+			dport: u16_be( &tcp[2..] )                 ,
+			seq  : u32_be( &tcp[4..] )                 ,
+			ack  : u32_be( &tcp[8..] )                 ,
+			flags: tcp[13]                             ,
+			data : tcp[20..].to_vec()                  ,
+		});
+
+		rest = &rest[ 16 + len.. ];
+	}
+
+	packets
+}
+
+
+
+#[ test ]
+//
+fn pcap()
+{
+	let recorder = Recorder::new();
+	let clock    = VirtualClock::new();
+
+	let (mut client, mut server) = EndpointBuilder::new()
+
+		.a_name  ( "client"         )
+		.b_name  ( "server"         )
+		.clock   ( clock.clone()    )
+		.recorder( recorder.clone() )
+		.build()
+	;
+
+	let mut buf = [0u8; 5];
+
+	clock.advance( Duration::from_millis( 1500 ) );
+	block_on( client.write_all( b"hello" ) ).expect( "write" );
+	block_on( server.read_exact( &mut buf ) ).expect( "read" );
+
+	clock.advance( Duration::from_millis( 2 ) );
+	block_on( server.write_all( b"hi" ) ).expect( "write" );
+	block_on( client.write_all( b"bye" ) ).expect( "write" );
+
+	let mut pcap = Vec::new();
+	recorder.transcript().write_pcap( &mut pcap, "server", 80 ).expect( "pcap" );
+
+	let packets = parse( &pcap );
+
+	assert_eq!( packets.len(), 6 );
+
+	let client_ip = [ 10, 0, 0, 1 ];
+	let server_ip = [ 10, 0, 0, 2 ];
+
+	// Handshake.
+	//
+	assert_eq!( packets[0].flags, 0x02 );
+	assert_eq!( packets[1].flags, 0x12 );
+	assert_eq!( packets[2].flags, 0x10 );
+
+	assert_eq!( (packets[0].src  , packets[0].dst  ), (client_ip, server_ip) );
+	assert_eq!( (packets[1].src  , packets[1].dst  ), (server_ip, client_ip) );
+	assert_eq!( (packets[0].sport, packets[0].dport), (49152, 80)            );
+
+	// Data.
+	//
+	let data: Vec<_> = packets[3..].iter().map( |p| ( p.sport, p.seq, p.ack, p.data.as_slice() ) ).collect();
+
+	assert_eq!( data, vec!
+	[
+		( 49152, 1, 1, &b"hello"[..] ),
+		( 80   , 1, 6, &b"hi"   [..] ),
+		( 49152, 6, 3, &b"bye"  [..] ),
+	]);
+
+	assert!( packets[3..].iter().all( |p| p.flags == 0x18 ) );
+
+	assert_eq!( packets[0].time, Duration::from_millis( 1500 ) );
+	assert_eq!( packets[4].time, Duration::from_millis( 1502 ) );
+}
+
+
+
+#[ test ]
+//
+fn many_clients()
+{
+	let recorder = Recorder::new();
+
+	for i in 0..16390
+	{
+		let (mut client, _server) = EndpointBuilder::new()
+
+			.a_name  ( format!( "client-{}", i ) )
+			.b_name  ( "server"                   )
+			.a_to_b  ( DirectionConfig::new( 1 )  )
+			.b_to_a  ( DirectionConfig::new( 1 )  )
+			.recorder( recorder.clone()           )
+			.build()
+		;
+
+		block_on( client.write_all( b"x" ) ).expect( "write" );
+	}
+
+	let mut pcap = Vec::new();
+	recorder.transcript().write_pcap( &mut pcap, "server", 80 ).expect( "pcap" );
+
+	let ports: Vec<_> = parse( &pcap ).iter().filter( |p| p.flags == 0x02 ).map( |p| p.sport ).collect();
+
+	// The server is the second endpoint, so it takes the slot of port 49153.
+	//
+	assert_eq!( ports.len()   , 16390 );
+	assert_eq!( ports[ 16382 ], 65535 );
+	assert_eq!( ports[ 16383 ], 49152 );
+}
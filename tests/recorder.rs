@@ -0,0 +1,100 @@
+// Tested:
+//
+// ✔ writes in both directions are recorded in order
+// ✔ only the bytes accepted by the buffer are recorded
+// ✔ timestamps from the clock
+// ✔ no timestamps without a clock
+// ✔ recording survives into_split
+//
+use
+{
+	futures_ringbuf :: { *                                                          } ,
+	futures         :: { AsyncWrite, AsyncReadExt, AsyncWriteExt, executor::block_on } ,
+	futures_test    :: { task::noop_waker                                           } ,
+	assert_matches  :: { assert_matches                                             } ,
+	std             :: { task::{ Context, Poll }, pin::Pin, time::Duration          } ,
+};
+
+
+
+#[ test ]
+//
+fn record()
+{
+	let recorder = Recorder::new();
+	let clock    = VirtualClock::new();
+
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_name  ( "server"                  )
+		.b_name  ( "client"                  )
+		.a_to_b  ( DirectionConfig::new( 4 ) )
+		.clock   ( clock.clone()             )
+		.recorder( recorder.clone()          )
+		.build()
+	;
+
+	let waker   = noop_waker();
+	let mut cx  = Context::from_waker( &waker );
+	let mut buf = [0u8; 10];
+
+	block_on( client.write_all( b"GET" ) ).expect( "write" );
+
+	clock.advance( Duration::from_millis( 5 ) );
+
+	// Only what fits in the buffer is recorded.
+	//
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, b"200 OK" ), Poll::Ready( Ok(4) ) );
+	assert!( Pin::new( &mut server ).poll_write( &mut cx, b"OK" ).is_pending() );
+
+	block_on( client.read( &mut buf ) ).expect( "read" );
+	block_on( server.write_all( b"OK" ) ).expect( "write" );
+
+	let transcript = recorder.transcript();
+	let records    = transcript.records();
+
+	assert_eq!( records.len(), 3 );
+
+	assert_eq!( records[0].seq()      , 0                                   );
+	assert_eq!( records[0].from()     , "client"                            );
+	assert_eq!( records[0].to()       , "server"                            );
+	assert_eq!( records[0].data()     , b"GET"                              );
+	assert_eq!( records[0].timestamp(), Some( Duration::ZERO )              );
+
+	assert_eq!( records[1].seq()      , 1                                   );
+	assert_eq!( records[1].from()     , "server"                            );
+	assert_eq!( records[1].data()     , b"200 "                             );
+	assert_eq!( records[1].timestamp(), Some( Duration::from_millis( 5 ) )  );
+
+	assert_eq!( records[2].data()     , b"OK"                               );
+
+	assert_eq!( transcript.bytes_written_by( "server" ), b"200 OK" );
+	assert_eq!( transcript.written_by( "client" ).count(), 1 );
+
+	assert!( transcript.to_string().starts_with( "#0 client -> server @ 0ns, 3 bytes\n" ) );
+}
+
+
+
+#[ test ]
+//
+fn no_clock()
+{
+	let recorder = Recorder::new();
+
+	let (a, _b) = EndpointBuilder::new().recorder( recorder.clone() ).build();
+
+	let (_reader, mut writer) = a.into_split();
+
+	block_on( writer.write_all( b"data" ) ).expect( "write" );
+
+	let transcript = recorder.transcript();
+
+	assert_eq!( transcript.records()[0].timestamp(), None    );
+	assert_eq!( transcript.records()[0].from()     , "a"     );
+	assert_eq!( transcript.records()[0].data()     , b"data" );
+
+	recorder.clear();
+
+	assert!( recorder.transcript().records().is_empty() );
+}
@@ -0,0 +1,145 @@
+// Tested:
+//
+// ✔ record a session, save it, load it and replay the server side
+// ✔ the client may write in other chunks than recorded
+// ✔ replay fails with InvalidData when the client writes something else
+// ✔ replay with a builder keeps its configuration
+//
+use
+{
+	futures_ringbuf :: { *                                                             } ,
+	futures         :: { AsyncReadExt, AsyncWriteExt, executor::block_on, future::join } ,
+	std             :: { io                                                            } ,
+};
+
+
+// A tiny line based protocol: the client sends requests, the server answers each line.
+//
+async fn client( endpoint: &mut Endpoint )
+{
+	let mut buf = [0u8; 3];
+
+	endpoint.write_all( b"HELLO\n" ).await.expect( "write" );
+	endpoint.read_exact( &mut buf ).await.expect( "read" );
+	assert_eq!( &buf, b"HI\n" );
+
+	endpoint.write_all( b"BY" ).await.expect( "write" );
+	endpoint.write_all( b"E\n" ).await.expect( "write" );
+	endpoint.read_exact( &mut buf ).await.expect( "read" );
+	assert_eq!( &buf, b"OK\n" );
+}
+
+
+async fn server( endpoint: &mut Endpoint )
+{
+	let mut buf = [0u8; 6];
+
+	endpoint.read_exact( &mut buf[..6] ).await.expect( "read" );
+	endpoint.write_all( b"HI\n" ).await.expect( "write" );
+
+	endpoint.read_exact( &mut buf[..4] ).await.expect( "read" );
+	endpoint.write_all( b"OK\n" ).await.expect( "write" );
+}
+
+
+fn record() -> Transcript
+{
+	let recorder = Recorder::new();
+
+	let (mut c, mut s) = EndpointBuilder::new()
+
+		.a_name  ( "client"         )
+		.b_name  ( "server"         )
+		.recorder( recorder.clone() )
+		.build()
+	;
+
+	block_on( join( client( &mut c ), server( &mut s ) ) );
+
+	recorder.transcript()
+}
+
+
+
+#[ test ]
+//
+fn replay()
+{
+	let path = std::env::temp_dir().join( format!( "futures_ringbuf_replay_{}.txt", std::process::id() ) );
+
+	record().save( &path ).expect( "save" );
+
+	let transcript = Transcript::load( &path ).expect( "load" );
+	std::fs::remove_file( &path ).expect( "remove transcript" );
+
+	assert_eq!( transcript.records().len(), 5 );
+
+	let (mut c, replay) = transcript.replay( "server" );
+
+	assert_eq!( c.name(), "client" );
+
+	let test = async
+	{
+		// Write in other chunks than recorded.
+		//
+		c.write_all( b"HEL" ).await.expect( "write" );
+		c.write_all( b"LO\n" ).await.expect( "write" );
+
+		let mut buf = [0u8; 3];
+		c.read_exact( &mut buf ).await.expect( "read" );
+		assert_eq!( &buf, b"HI\n" );
+
+		c.write_all( b"BYE\n" ).await.expect( "write" );
+
+		let mut rest = Vec::new();
+		c.read_to_end( &mut rest ).await.expect( "read" );
+		assert_eq!( rest, b"OK\n" );
+	};
+
+	let ((), result) = block_on( join( test, replay ) );
+
+	result.expect( "replay" );
+}
+
+
+
+#[ test ]
+//
+fn unexpected()
+{
+	let (mut c, replay) = record().replay( "server" );
+
+	let test = async
+	{
+		c.write_all( b"HELP!\n" ).await.expect( "write" );
+
+		// The peer hangs up.
+		//
+		let mut buf = Vec::new();
+		assert_eq!( c.read_to_end( &mut buf ).await.expect( "read" ), 0 );
+	};
+
+	let ((), result) = block_on( join( test, replay ) );
+
+	let err = result.expect_err( "replay should fail" );
+
+	assert_eq!( err.kind(), io::ErrorKind::InvalidData );
+	assert!( err.to_string().contains( "offset 3.\nexpected: b\"LO\\n\"\n     got: b\"P!\\n\"" ) );
+}
+
+
+
+#[ test ]
+//
+fn replay_with()
+{
+	let recorder = Recorder::new();
+	let builder  = EndpointBuilder::new().recorder( recorder.clone() );
+
+	let (mut c, replay) = record().replay_with( builder, "server" );
+
+	block_on( join( client( &mut c ), replay ) ).1.expect( "replay" );
+
+	assert_eq!( recorder.transcript().bytes_written_by( "server" ), b"HI\nOK\n"     );
+	assert_eq!( recorder.transcript().bytes_written_by( "client" ), b"HELLO\nBYE\n" );
+}
@@ -0,0 +1,447 @@
+#![ cfg( feature = "sketchy" ) ]
+
+// Tested:
+//
+// ✔ the default config still delivers all data
+// ✔ a reliable config forwards every call unmodified
+// ✔ PartialSize::OneByte reads one byte at a time
+// ✔ PartialSize::UpTo limits the size of partial writes
+// ✔ heavy_pending returns pending most of the time
+// ✔ invalid probabilities panic
+// ✔ Sink and Stream deliver all items
+// ✔ Future returns pending and then the output of the inner future
+// ✔ transient errors can be retried
+// ✔ after a fatal error, all operations fail
+// ✔ spurious wake ups happen even when ready
+// ✔ deferred wake ups wait for wake_all or a poll of another Sketchy
+// ✔ delayed wake ups come from the clock
+// ✔ data arrives with delayed wake ups on the system clock
+//
+use
+{
+	futures_ringbuf :: { *                                                      } ,
+	futures         :: { AsyncRead, AsyncWrite, AsyncReadExt, AsyncWriteExt     } ,
+	futures         :: { SinkExt, StreamExt, stream                             } ,
+	futures         :: { executor::block_on, future::{ join, ready }            } ,
+	futures_test    :: { task::{ noop_context, new_count_waker }                } ,
+	std             :: { pin::Pin, future::Future, task::{ Context, Poll }, io  } ,
+	std             :: { time::Duration                                         } ,
+};
+
+
+
+#[ test ]
+//
+fn default_config() { block_on( async
+{
+	for seed in 0..50
+	{
+		let (server, client) = Endpoint::pair( 10, 10 );
+
+		let mut server = Sketchy::with_config( server, seed, SketchyConfig::default() );
+		let mut client = Sketchy::with_config( client, seed, SketchyConfig::default() );
+
+		let svr = async move
+		{
+			server.write_all( b"Hello World" ).await.expect( "write" );
+			server.close().await.expect( "close" );
+		};
+
+		let clt = async move
+		{
+			let mut read = Vec::new();
+
+			client.read_to_end( &mut read ).await.expect( "read" );
+			assert_eq!( read, b"Hello World", "seed: {}", seed );
+		};
+
+		join( svr, clt ).await;
+	}
+})}
+
+
+
+#[ test ]
+//
+fn reliable() { block_on( async
+{
+	let (server, client) = Endpoint::pair( 64, 64 );
+
+	let mut server = Sketchy::with_config( server, 1, SketchyConfig::reliable() );
+	let mut client = Sketchy::with_config( client, 1, SketchyConfig::reliable() );
+
+	for _ in 0..20
+	{
+		let mut buf = [0u8; 5];
+
+		assert_eq!( server.write( b"12345" ).await.expect( "write" ), 5 );
+		assert_eq!( client.read( &mut buf ).await.expect( "read" ), 5 );
+		assert_eq!( &buf, b"12345" );
+	}
+})}
+
+
+
+#[ test ]
+//
+fn one_byte() { block_on( async
+{
+	let (mut server, client) = Endpoint::pair( 64, 64 );
+
+	let config = SketchyConfig::reliable().read( SketchyOp::new( 0.0, 1.0 ).size( PartialSize::OneByte ) );
+	let mut client = Sketchy::with_config( client, 1, config );
+
+	server.write_all( b"Hello" ).await.expect( "write" );
+
+	let mut buf = [0u8; 5];
+
+	for i in 0..5
+	{
+		assert_eq!( client.read( &mut buf[i..] ).await.expect( "read" ), 1 );
+	}
+
+	assert_eq!( &buf, b"Hello" );
+})}
+
+
+
+#[ test ]
+//
+fn up_to() { block_on( async
+{
+	let (server, mut client) = Endpoint::pair( 1024, 1024 );
+
+	let config = SketchyConfig::reliable().write( SketchyOp::new( 0.0, 1.0 ).size( PartialSize::UpTo( 3 ) ) );
+	let mut server = Sketchy::with_config( server, 7, config );
+
+	let data = [7u8; 100];
+
+	for _ in 0..50
+	{
+		let n = server.write( &data ).await.expect( "write" );
+
+		assert!( ( 1..=3 ).contains( &n ), "wrote {} bytes", n );
+	}
+
+	// With a buffer of a single byte, we can't make it partial.
+	//
+	assert_eq!( server.write( &data[..1] ).await.expect( "write" ), 1 );
+
+	server.close().await.expect( "close" );
+
+	let mut read = Vec::new();
+	client.read_to_end( &mut read ).await.expect( "read" );
+
+	assert!( read.iter().all( |b| *b == 7 ) );
+})}
+
+
+
+#[ test ]
+//
+fn heavy_pending()
+{
+	let (server, _client) = Endpoint::pair( 64, 64 );
+
+	let mut server  = Sketchy::with_config( server, 3, SketchyConfig::heavy_pending() );
+	let mut cx      = noop_context();
+	let mut pending = 0;
+
+	for _ in 0..100
+	{
+		if Pin::new( &mut server ).poll_flush( &mut cx ).is_pending()
+		{
+			pending += 1;
+		}
+	}
+
+	assert!( pending > 60, "pending {} times out of 100", pending );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "probability must be between 0 and 1" ) ]
+//
+fn invalid_probability()
+{
+	let _ = SketchyOp::new( 0.5, 1.5 );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "probability must be between 0 and 1" ) ]
+//
+fn invalid_release_probability()
+{
+	let _ = WakeMode::deferred( &DeferredWakes::new(), -0.1 );
+}
+
+
+
+#[ test ]
+//
+fn sink_stream() { block_on( async
+{
+	for seed in 0..50
+	{
+		let (reader, writer) = RingBuffer::<u32>::new( 4 ).split();
+
+		let mut writer = Sketchy::new( writer, seed );
+		let     reader = Sketchy::new( reader, seed );
+
+		let send = async move
+		{
+			writer.send_all( &mut stream::iter( 0..100 ).map( Ok ) ).await.expect( "send" );
+			SinkExt::close( &mut writer ).await.expect( "close" );
+		};
+
+		let (_, received) = join( send, reader.collect::<Vec<u32>>() ).await;
+
+		assert_eq!( received, ( 0..100 ).collect::<Vec<_>>(), "seed: {}", seed );
+	}
+})}
+
+
+
+#[ test ]
+//
+fn future()
+{
+	let mut fut     = Sketchy::with_config( ready( 5 ), 3, SketchyConfig::heavy_pending() );
+	let mut cx      = noop_context();
+	let mut pending = 0;
+
+	let out = loop
+	{
+		match Pin::new( &mut fut ).poll( &mut cx )
+		{
+			Poll::Ready(out) => break out,
+			Poll::Pending    => pending += 1,
+		}
+	};
+
+	assert_eq!( out, 5 );
+	assert!( pending > 0 );
+}
+
+
+
+#[ test ]
+//
+fn transient_error() { block_on( async
+{
+	let (mut server, client) = Endpoint::pair( 64, 64 );
+
+	let config = SketchyConfig::reliable()
+
+		.read( SketchyOp::reliable().transient_error( 0.5, io::ErrorKind::Interrupted ) )
+	;
+
+	let mut client = Sketchy::with_config( client, 5, config );
+	let mut errors = 0;
+	let mut read   = Vec::new();
+
+	server.write_all( b"Hello World" ).await.expect( "write" );
+	server.close().await.expect( "close" );
+
+	loop
+	{
+		let mut buf = [0u8; 4];
+
+		match client.read( &mut buf ).await
+		{
+			Ok(0) => break,
+			Ok(n) => read.extend_from_slice( &buf[..n] ),
+
+			Err(e) =>
+			{
+				assert_eq!( e.kind(), io::ErrorKind::Interrupted );
+				errors += 1;
+			}
+		}
+	}
+
+	assert_eq!( read, b"Hello World" );
+	assert!( errors > 0 );
+	assert!( !client.is_broken() );
+})}
+
+
+
+#[ test ]
+//
+fn fatal_error() { block_on( async
+{
+	let (server, mut client) = Endpoint::pair( 64, 64 );
+
+	let config = SketchyConfig::reliable()
+
+		.write( SketchyOp::reliable().fatal_error( 1.0, io::ErrorKind::ConnectionReset ) )
+	;
+
+	let mut server = Sketchy::with_config( server, 5, config );
+
+	let err = server.write( b"Hello" ).await.expect_err( "write" );
+	assert_eq!( err.kind(), io::ErrorKind::ConnectionReset );
+	assert!( server.is_broken() );
+
+	let mut buf = [0u8; 5];
+
+	assert_eq!( server.read ( &mut buf ).await.expect_err( "read"  ).kind(), io::ErrorKind::ConnectionReset );
+	assert_eq!( server.flush(           ).await.expect_err( "flush" ).kind(), io::ErrorKind::ConnectionReset );
+	assert_eq!( server.close(           ).await.expect_err( "close" ).kind(), io::ErrorKind::ConnectionReset );
+
+	// Nothing was written to the wrapped endpoint.
+	//
+	drop( server );
+	assert_eq!( client.read( &mut buf ).await.expect( "read" ), 0 );
+})}
+
+
+
+#[ test ]
+//
+fn spurious_wakes() { block_on( async
+{
+	let (mut server, client) = Endpoint::pair( 64, 64 );
+
+	let mut client     = Sketchy::with_config( client, 1, SketchyConfig::reliable().spurious_wakes( 1.0 ) );
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let mut buf        = [0u8; 5];
+
+	server.write_all( b"Hello" ).await.expect( "write" );
+
+	match Pin::new( &mut client ).poll_read( &mut cx, &mut buf )
+	{
+		Poll::Ready(n) => assert_eq!( n.expect( "read" ), 5 ),
+		Poll::Pending  => panic!( "should be ready" ),
+	}
+
+	assert_eq!( count.get(), 1 );
+})}
+
+
+
+/// Poll a read on `sketchy` until it returns pending.
+//
+fn poll_until_pending( sketchy: &mut Sketchy<Endpoint>, cx: &mut Context<'_> )
+{
+	let mut buf = [0u8; 5];
+
+	while Pin::new( &mut *sketchy ).poll_read( cx, &mut buf ).is_ready() {}
+}
+
+
+
+#[ test ]
+//
+fn deferred_wakes()
+{
+	let wakers = DeferredWakes::new();
+	let config = SketchyConfig::reliable()
+
+		.read( SketchyOp::new( 1.0, 0.0 ) )
+		.wake( WakeMode::deferred( &wakers, 0.5 ) )
+	;
+
+	let (a, b) = Endpoint::pair( 64, 64 );
+	let mut a  = Sketchy::with_config( a, 1, config.clone() );
+	let mut b  = Sketchy::with_config( b, 2, config         );
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+
+	poll_until_pending( &mut a, &mut cx );
+
+	assert_eq!( count.get() , 0 );
+	assert_eq!( wakers.len(), 1 );
+
+	wakers.wake_all();
+
+	assert_eq!( count.get(), 1 );
+	assert!( wakers.is_empty() );
+
+	// Now let the other Sketchy release the wake up.
+	//
+	poll_until_pending( &mut a, &mut cx );
+
+	let mut noop = noop_context();
+
+	while count.get() == 1
+	{
+		poll_until_pending( &mut b, &mut noop );
+	}
+
+	assert_eq!( count.get(), 2 );
+}
+
+
+
+#[ test ]
+//
+fn delayed_wakes()
+{
+	let clock  = VirtualClock::new();
+	let config = SketchyConfig::reliable()
+
+		.read( SketchyOp::new( 1.0, 0.0 ) )
+		.wake( WakeMode::delayed( clock.clone(), Duration::from_millis( 10 ) ) )
+	;
+
+	let (a, _b) = Endpoint::pair( 64, 64 );
+	let mut a   = Sketchy::with_config( a, 1, config );
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+
+	poll_until_pending( &mut a, &mut cx );
+
+	assert_eq!( clock.pending_timers() + count.get(), 1 );
+
+	clock.advance( Duration::from_millis( 10 ) );
+
+	assert_eq!( count.get(), 1 );
+}
+
+
+
+#[ test ]
+//
+fn delayed_system_clock() { block_on( async
+{
+	for seed in 0..10
+	{
+		let clock  = SystemClock::new();
+		let config = SketchyConfig::heavy_pending()
+
+			.wake          ( WakeMode::delayed( clock, Duration::from_millis( 1 ) ) )
+			.spurious_wakes( 0.2                                                   )
+		;
+
+		let (server, client) = Endpoint::pair( 10, 10 );
+
+		let mut server = Sketchy::with_config( server, seed, config.clone() );
+		let mut client = Sketchy::with_config( client, seed, config         );
+
+		let svr = async move
+		{
+			server.write_all( b"Hello World" ).await.expect( "write" );
+			server.close().await.expect( "close" );
+		};
+
+		let clt = async move
+		{
+			let mut read = Vec::new();
+
+			client.read_to_end( &mut read ).await.expect( "read" );
+			assert_eq!( read, b"Hello World", "seed: {}", seed );
+		};
+
+		join( svr, clt ).await;
+	}
+})}
@@ -0,0 +1,156 @@
+// Tested:
+//
+// ✔ writes are limited to the burst size, then pending until tokens are refilled
+// ✔ pending writer woken up when it may write again
+// ✔ polling a pending writer again doesn't add timers
+// ✔ the default burst is a tenth of a second worth of data
+// ✔ the other direction is not affected
+// ✔ bandwidth with the default system clock
+//
+use
+{
+	futures_ringbuf :: { *                                                                   } ,
+	futures         :: { AsyncWrite, AsyncReadExt, AsyncWriteExt, executor::block_on         } ,
+	futures_test    :: { task::new_count_waker                                               } ,
+	assert_matches  :: { assert_matches                                                      } ,
+	std             :: { task::{ Context, Poll }, pin::Pin, time::{ Duration, Instant }      } ,
+};
+
+
+
+#[ test ]
+//
+fn bandwidth()
+{
+	let clock = VirtualClock::new();
+
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( 100 ).bandwidth( 100 ).burst( 20 ) )
+		.clock ( clock.clone() )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let     data       = [7u8; 50];
+
+	// The bucket starts full.
+	//
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &data ), Poll::Ready( Ok(20) ) );
+	assert!( Pin::new( &mut server ).poll_write( &mut cx, &data ).is_pending() );
+
+	// 100 bytes per second is a byte every 10ms.
+	//
+	clock.advance( Duration::from_millis( 5 ) );
+	assert_eq!( count.get(), 0 );
+
+	clock.advance( Duration::from_millis( 5 ) );
+	assert_eq!( count.get(), 1 );
+
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &data ), Poll::Ready( Ok(1) ) );
+	assert!( Pin::new( &mut server ).poll_write( &mut cx, &data ).is_pending() );
+
+	// The bucket doesn't grow beyond the burst size.
+	//
+	clock.advance( Duration::from_secs( 10 ) );
+	assert_eq!( count.get(), 2 );
+
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &data ), Poll::Ready( Ok(20) ) );
+
+	let mut buf = [0u8; 100];
+	assert_eq!( block_on( client.read( &mut buf ) ).expect( "read" ), 41 );
+
+
+	// The other direction is not limited.
+	//
+	block_on( client.write_all( &data ) ).expect( "write" );
+	assert_eq!( block_on( server.read( &mut buf ) ).expect( "read" ), 50 );
+}
+
+
+
+#[ test ]
+//
+fn one_timer()
+{
+	let clock = VirtualClock::new();
+
+	let (mut server, _client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( 100 ).bandwidth( 100 ).burst( 10 ) )
+		.clock ( clock.clone() )
+		.build()
+	;
+
+	let (waker, count) = new_count_waker();
+	let mut cx         = Context::from_waker( &waker );
+	let     data       = [7u8; 50];
+
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &data ), Poll::Ready( Ok(10) ) );
+
+	for _ in 0..5
+	{
+		assert!( Pin::new( &mut server ).poll_write( &mut cx, &data ).is_pending() );
+	}
+
+	assert_eq!( clock.pending_timers(), 1 );
+
+	// A different task polling needs a timer of its own.
+	//
+	let (other, _) = new_count_waker();
+
+	assert!( Pin::new( &mut server ).poll_write( &mut Context::from_waker( &other ), &data ).is_pending() );
+	assert_eq!( clock.pending_timers(), 2 );
+
+	clock.advance( Duration::from_millis( 10 ) );
+
+	assert_eq!( count.get(), 1 );
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &data ), Poll::Ready( Ok(1) ) );
+}
+
+
+
+#[ test ]
+//
+fn default_burst()
+{
+	let clock = VirtualClock::new();
+
+	let (mut server, _client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( 100 ).bandwidth( 500 ) )
+		.clock ( clock.clone() )
+		.build()
+	;
+
+	let (waker, _count) = new_count_waker();
+	let mut cx          = Context::from_waker( &waker );
+
+	assert_matches!( Pin::new( &mut server ).poll_write( &mut cx, &[0u8; 100] ), Poll::Ready( Ok(50) ) );
+	assert!( Pin::new( &mut server ).poll_write( &mut cx, &[0u8; 100] ).is_pending() );
+}
+
+
+
+#[ cfg(not( target_arch = "wasm32" )) ]
+//
+#[ test ]
+//
+fn system_clock()
+{
+	let (mut server, mut client) = EndpointBuilder::new()
+
+		.a_to_b( DirectionConfig::new( 100 ).bandwidth( 1000 ).burst( 10 ) )
+		.build()
+	;
+
+	let start = Instant::now();
+
+	block_on( server.write_all( &[0u8; 60] ) ).expect( "write" );
+
+	assert!( start.elapsed() >= Duration::from_millis( 45 ) );
+
+	let mut buf = [0u8; 60];
+	block_on( client.read_exact( &mut buf ) ).expect( "read" );
+}
@@ -0,0 +1,113 @@
+#![ cfg( feature = "tokio" ) ]
+
+// Tested:
+//
+// ✔ basic sending and receiving without compat
+// ✔ framing an endpoint with a tokio codec without compat
+// ✔ reading remaining data after shutdown
+// ✔ Sketchy with the tokio traits
+//
+use
+{
+	futures_ringbuf :: { *                                                            } ,
+	tokio_util      :: { codec::{ Framed, LinesCodec }                                } ,
+	tokio::io       :: { AsyncWriteExt, AsyncReadExt                                  } ,
+	futures         :: { future::join, SinkExt, StreamExt, executor::block_on         } ,
+};
+
+
+
+#[ test ]
+//
+fn basic_usage() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 10, 10 );
+
+	let     data = vec![ 1,2,3 ];
+	let mut read = [0u8;3];
+
+	server.write_all( &data ).await.expect( "write" );
+
+	let n = client.read( &mut read ).await.expect( "read" );
+	assert_eq!( n   , 3                 );
+	assert_eq!( read, vec![ 1,2,3 ][..] );
+})}
+
+
+
+#[ test ]
+//
+fn framed() { block_on( async
+{
+	let (server, client) = Endpoint::pair( 10, 10 );
+
+	let svr = async move
+	{
+		let mut framed = Framed::new( server, LinesCodec::new() );
+
+		framed.send( "Hello World" ).await.expect( "send" );
+		SinkExt::<&str>::close( &mut framed ).await.expect( "close" );
+	};
+
+	let clt = async move
+	{
+		let mut framed = Framed::new( client, LinesCodec::new() );
+
+		assert_eq!( framed.next().await.transpose().expect( "receive" ), Some( "Hello World".to_string() ) );
+		assert!( framed.next().await.is_none() );
+	};
+
+	join( svr, clt ).await;
+})}
+
+
+
+#[ test ]
+//
+fn shutdown_read_remaining() { block_on( async
+{
+	let (mut server, mut client) = Endpoint::pair( 10, 10 );
+
+	let mut read = Vec::new();
+
+	server.write_all( &[ 1,2,3 ] ).await.expect( "write" );
+	server.shutdown().await.expect( "shutdown" );
+
+	client.read_to_end( &mut read ).await.expect( "read" );
+	assert_eq!( read, vec![ 1,2,3 ] );
+})}
+
+
+
+#[ cfg( feature = "sketchy" ) ]
+//
+#[ test ]
+//
+fn sketchy() { block_on( async
+{
+	for _ in 0..50
+	{
+		let seed = Dictator::new_seed();
+
+		let (server, client) = Endpoint::pair( 10, 10 );
+
+		let mut server = Sketchy::new( server, seed );
+		let mut client = Sketchy::new( client, seed );
+
+		let svr = async move
+		{
+			server.write_all( b"Hello World" ).await.expect( "write" );
+			server.shutdown().await.expect( "shutdown" );
+		};
+
+		let clt = async move
+		{
+			let mut read = Vec::new();
+
+			client.read_to_end( &mut read ).await.expect( "read" );
+			assert_eq!( read, b"Hello World", "seed: {}", seed );
+		};
+
+		join( svr, clt ).await;
+	}
+})}
@@ -0,0 +1,266 @@
+#![ cfg( not( target_arch = "wasm32" ) ) ]
+
+// Tested:
+//
+// ✔ types that store the waker pass the audit
+// ✔ returning pending without keeping the waker panics
+// ✔ report_only counts lost wake ups
+// ✔ waking up right away is fine
+// ✔ Stream and Sink are audited
+// ✔ block_on_audited runs futures to completion
+// ✔ block_on_audited detects a deadlock caused by a lost wake up
+// ✔ block_on_audited waits for wake ups from another thread
+// ✔ block_on_audited doesn't see a deadlock when another thread wakes up and drops the waker right away
+// ✔ Sketchy passes the audit
+//
+use
+{
+	futures_ringbuf :: { *                                                     } ,
+	futures         :: { AsyncRead, AsyncReadExt, AsyncWriteExt, SinkExt       } ,
+	futures         :: { StreamExt, executor::block_on, join                   } ,
+	futures         :: { future::poll_fn                                       } ,
+	std             :: { io, pin::Pin, task::{ Context, Poll }, time::Duration } ,
+};
+
+
+/// A reader that forgets to register the waker.
+//
+struct Forgetful;
+
+impl AsyncRead for Forgetful
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, _buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		// Cloning without storing doesn't count.
+		//
+		let _ = cx.waker().clone();
+
+		Poll::Pending
+	}
+}
+
+
+/// A reader that is always pending but wakes up the task right away.
+//
+struct Eager;
+
+impl AsyncRead for Eager
+{
+	fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, _buf: &mut [u8] ) -> Poll< io::Result<usize> >
+	{
+		cx.waker().wake_by_ref();
+
+		Poll::Pending
+	}
+}
+
+
+
+#[ test ]
+//
+fn endpoint()
+{
+	let (server, client) = Endpoint::pair( 4, 4 );
+
+	let mut server = WakerAudit::new( server );
+	let mut client = WakerAudit::new( client );
+
+	block_on( async
+	{
+		let write = async
+		{
+			server.write_all( b"Hello World" ).await.expect( "write" );
+			server.close().await.expect( "close" );
+		};
+
+		let mut read = Vec::new();
+
+		let (_, n) = join!( write, client.read_to_end( &mut read ) );
+
+		assert_eq!( n.expect( "read" ), 11 );
+		assert_eq!( read, b"Hello World" );
+	});
+
+	assert_eq!( server.lost_wakeups(), 0 );
+	assert_eq!( client.lost_wakeups(), 0 );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "AsyncRead::poll_read returned Poll::Pending without storing or waking up the waker" ) ]
+//
+fn forgetful()
+{
+	let mut reader = WakerAudit::new( Forgetful );
+	let mut buf    = [0u8; 4];
+
+	let _ = Pin::new( &mut reader ).poll_read( &mut futures_test::task::noop_context(), &mut buf );
+}
+
+
+
+#[ test ]
+//
+fn report_only()
+{
+	let mut reader = WakerAudit::new( Forgetful ).report_only();
+	let mut buf    = [0u8; 4];
+	let mut cx     = futures_test::task::noop_context();
+
+	assert!( Pin::new( &mut reader ).poll_read( &mut cx, &mut buf ).is_pending() );
+	assert!( Pin::new( &mut reader ).poll_read( &mut cx, &mut buf ).is_pending() );
+
+	assert_eq!( reader.lost_wakeups(), 2 );
+}
+
+
+
+#[ test ]
+//
+fn eager()
+{
+	let mut reader = WakerAudit::new( Eager );
+	let mut buf    = [0u8; 4];
+
+	assert!( Pin::new( &mut reader ).poll_read( &mut futures_test::task::noop_context(), &mut buf ).is_pending() );
+	assert_eq!( reader.lost_wakeups(), 0 );
+}
+
+
+
+#[ test ]
+//
+fn sink_stream()
+{
+	let (reader, writer) = RingBuffer::<u32>::new( 2 ).split();
+
+	let mut writer = WakerAudit::new( writer );
+	let     reader = WakerAudit::new( reader );
+
+	let received = block_on_audited( async
+	{
+		let send = async
+		{
+			for i in 0..10
+			{
+				writer.send( i ).await.expect( "send" );
+			}
+
+			writer.close().await.expect( "close" );
+		};
+
+		join!( send, reader.collect::<Vec<u32>>() ).1
+	});
+
+	assert_eq!( received, ( 0..10 ).collect::<Vec<_>>() );
+}
+
+
+
+#[ test ]
+//
+#[ should_panic( expected = "deadlock" ) ]
+//
+fn lost_wakeup_deadlock()
+{
+	block_on_audited( async
+	{
+		let mut buf = [0u8; 1];
+
+		let _ = Forgetful.read( &mut buf ).await;
+	});
+}
+
+
+
+#[ test ]
+//
+fn other_thread()
+{
+	let (mut server, mut client) = Endpoint::pair( 4, 4 );
+
+	let writer = std::thread::spawn( move ||
+	{
+		std::thread::sleep( Duration::from_millis( 50 ) );
+
+		block_on( server.write_all( b"Hi" ) ).expect( "write" );
+	});
+
+	let mut buf = [0u8; 2];
+
+	block_on_audited( client.read_exact( &mut buf ) ).expect( "read" );
+
+	assert_eq!( &buf, b"Hi" );
+
+	writer.join().expect( "join" );
+}
+
+
+
+#[ test ]
+//
+fn other_thread_wake_and_drop()
+{
+	let (tx, rx) = std::sync::mpsc::channel::<std::task::Waker>();
+
+	let waker = std::thread::spawn( move ||
+	{
+		for waker in rx
+		{
+			waker.wake();
+		}
+	});
+
+	let mut polls = 0;
+
+	block_on_audited( poll_fn( |cx|
+	{
+		polls += 1;
+
+		if polls > 20_000
+		{
+			return Poll::Ready(());
+		}
+
+		tx.send( cx.waker().clone() ).expect( "send waker" );
+
+		Poll::Pending
+	}));
+
+	drop( tx );
+	waker.join().expect( "join" );
+}
+
+
+
+#[ cfg( feature = "sketchy" ) ]
+//
+#[ test ]
+//
+fn sketchy()
+{
+	for seed in 0..20
+	{
+		let (server, client) = Endpoint::pair( 4, 4 );
+
+		let mut server = WakerAudit::new( Sketchy::with_config( server, seed, SketchyConfig::heavy_pending() ) );
+		let mut client = WakerAudit::new( Sketchy::with_config( client, seed, SketchyConfig::heavy_pending() ) );
+
+		block_on_audited( async
+		{
+			let write = async
+			{
+				server.write_all( b"Hello World" ).await.expect( "write" );
+				server.close().await.expect( "close" );
+			};
+
+			let mut read = Vec::new();
+
+			let (_, n) = join!( write, client.read_to_end( &mut read ) );
+
+			assert_eq!( n.expect( "read" ), 11, "seed: {}", seed );
+		});
+	}
+}